@@ -0,0 +1,78 @@
+use std::time::Instant;
+
+use crate::{logger::logger_manager::Logger, persistent::record::PersistentRecord};
+
+use super::{
+    cache::{CacheError, CacheType, EntryMeta},
+    string_value::live_string_entry,
+    Cache,
+};
+
+pub trait Append {
+    fn append(
+        &mut self,
+        cluster: &str,
+        key: &str,
+        value: &[u8],
+        ignore_persistent: bool,
+    ) -> Result<usize, CacheError>;
+}
+
+impl Append for Cache {
+    fn append(
+        &mut self,
+        cluster: &str,
+        key: &str,
+        value: &[u8],
+        ignore_persistent: bool,
+    ) -> Result<usize, CacheError> {
+        let cluster = &self.resolve_cluster(cluster);
+        if !self.has_memory_for_write(cluster, ignore_persistent) {
+            return Err(CacheError::MemoryLimitExceeded);
+        }
+
+        let next_version = self.next_version();
+        let mut store = self.store.lock().unwrap();
+        self.check_cluster_write(&mut store, cluster, Some(key), ignore_persistent)?;
+        let new_len = match live_string_entry(store.get_mut(cluster), key)? {
+            Some((existing_value, _, _, _, version, meta)) => {
+                existing_value.extend_from_slice(value);
+                *version = next_version;
+                meta.touch_write();
+                existing_value.len()
+            }
+            None => {
+                let ttl = self.cluster_default_ttl(cluster);
+                let new_value = value.to_vec();
+                self.memory_handler
+                    .lock()
+                    .unwrap()
+                    .add_memory(std::mem::size_of_val(&new_value));
+                store.entry(cluster.to_string()).or_default().insert(
+                    key.to_string(),
+                    (
+                        new_value,
+                        ttl.map(|duration| Instant::now() + duration),
+                        ttl,
+                        CacheType::Str,
+                        next_version,
+                        EntryMeta::new(),
+                    ),
+                );
+                value.len()
+            }
+        };
+
+        if self.enable_log {
+            Logger::log_info("value appended").write_log_to_file();
+        }
+        self.persist(
+            PersistentRecord::new("APPEND")
+                .arg(cluster)
+                .arg(key)
+                .arg(value),
+            ignore_persistent,
+        );
+        Ok(new_len)
+    }
+}
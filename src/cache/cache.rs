@@ -1,25 +1,45 @@
 use crate::convert::vec_to_i32;
 use crate::creds::cred_manager::CredsManager;
-use crate::known_directories::KNOWN_DIRECTORIES;
+use crate::jobs::delete_by_pattern_job::DeleteByPatternProgress;
 use crate::logger::logger_manager::Logger;
 use crate::memory_handling;
-use chrono::prelude::*;
+use crate::persistent::persistent_Manager::{self, LogPosition};
+use crate::persistent::record::{self, PersistentRecord, ReadRecord, RecordReader};
+use crate::persistent::snapshot;
+use chrono::{Local, TimeZone};
 use core::str;
 use rand::seq::SliceRandom;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::OpenOptions;
-use std::io::{BufRead, BufReader};
+use std::io::{BufReader, Seek, SeekFrom};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
+use super::append::Append;
 use super::clear_all::ClearAll;
 use super::clear_cluster::ClearCluster;
-use super::decr::Decr;
+use super::cluster_alias::ClusterAlias;
+use super::cluster_config::{ClusterConfig, ClusterSettings};
+use super::copy_key::CopyKey;
 use super::delete::Delete;
-use super::incr::Incr;
+use super::distributed_lock::LockEntry;
+use super::drop_cluster::DropCluster;
+use super::expire_cluster::ExpireCluster;
+use super::expire_key::ExpireKey;
+use super::move_cluster::{ClusterWritePolicy, CopyCluster};
+use super::move_del_cluster::MoveCluster;
+use super::move_key::MoveKey;
+use super::rate_limit::RateLimitState;
+use super::rename_key::RenameKey;
+use super::script::{ScriptCache, ScriptLimits};
 use super::set::Set;
+use super::set_cluster::SetCluster;
+use super::set_range::SetRange;
+use super::snapshot::Snapshot;
+use super::tag_index::{TagIndex, Tagging};
 
 #[derive(Clone, Serialize, Debug)]
 pub enum CacheType {
@@ -42,28 +62,142 @@ impl CacheType {
     }
 }
 
+#[derive(Clone, Copy, Serialize, Debug, PartialEq)]
+pub enum CacheError {
+    WrongType,
+    KeyNotFound,
+    ClusterNotFound,
+    AliasConflict,
+    ReadOnlyCluster,
+    ClusterFull,
+    InvalidClusterConfig,
+    MemoryLimitExceeded,
+    TtlOutOfRange,
+    OffsetOutOfRange,
+    InvalidTag,
+}
+
+impl CacheError {
+    pub fn as_str(&self) -> &str {
+        match self {
+            CacheError::WrongType => "operation against a key holding the wrong kind of value",
+            CacheError::KeyNotFound => "key not found",
+            CacheError::ClusterNotFound => "cluster not found",
+            CacheError::AliasConflict => "alias name is already used by a cluster",
+            CacheError::ReadOnlyCluster => "cluster is read only",
+            CacheError::ClusterFull => "cluster has reached its max keys",
+            CacheError::InvalidClusterConfig => "invalid cluster configuration",
+            CacheError::MemoryLimitExceeded => "memory usage has exceeded the configured limit",
+            CacheError::TtlOutOfRange => "ttl is out of range",
+            CacheError::OffsetOutOfRange => "offset is out of range",
+            CacheError::InvalidTag => "tags can not be empty",
+        }
+    }
+}
+
+// bookkeeping kept next to every value for inspection
+#[derive(Clone, Debug)]
+pub struct EntryMeta {
+    pub created_at: SystemTime,
+    pub updated_at: SystemTime,
+    pub last_access: SystemTime,
+    pub access_count: u64,
+    pub tags: Vec<String>,
+}
+
+impl EntryMeta {
+    pub fn new() -> Self {
+        let now = SystemTime::now();
+        EntryMeta {
+            created_at: now,
+            updated_at: now,
+            last_access: now,
+            access_count: 0,
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn touch_write(&mut self) {
+        self.updated_at = SystemTime::now();
+    }
+
+    // a copy starts a fresh history but keeps depending on the same tags
+    pub fn copied(&self) -> Self {
+        EntryMeta {
+            tags: self.tags.clone(),
+            ..EntryMeta::new()
+        }
+    }
+
+    pub fn touch_read(&mut self) {
+        self.last_access = SystemTime::now();
+        self.access_count += 1;
+    }
+}
+
+impl Default for EntryMeta {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// value, expiration time, ttl, type, version, metadata
+pub type CacheEntry = (
+    Vec<u8>,
+    Option<Instant>,
+    Option<Duration>,
+    CacheType,
+    u64,
+    EntryMeta,
+);
+pub fn is_live(entry: &CacheEntry, now: Instant) -> bool {
+    entry.1.map_or(true, |exp| exp > now)
+}
+
+// clusters and their keys are kept ordered so scans can resume from a cursor key
+pub type ClusterStore = BTreeMap<String, CacheEntry>;
+pub type Store = BTreeMap<String, ClusterStore>;
+
 #[derive(Clone, Serialize)]
 pub struct ResultValue {
     pub value: Option<Vec<u8>>,
     pub value_type: Option<CacheType>,
+    pub version: Option<u64>,
+}
+
+// what startup recovery found on disk, reported once all persistent files are replayed
+#[derive(Default, Debug, Serialize)]
+pub struct RecoverySummary {
+    pub snapshot_loaded: bool,
+    pub files: usize,
+    pub migrated: usize,
+    pub applied: usize,
+    pub skipped: usize,
+    pub truncated: usize,
+    // records left out because they were written after the point in time being recovered
+    pub after_target: usize,
 }
 
 #[derive(Clone)]
 pub struct Cache {
     pub evict_type: i32,
-    pub store: Arc<
-        Mutex<
-            HashMap<
-                String,
-                HashMap<String, (Vec<u8>, Option<Instant>, Option<Duration>, CacheType)>,
-            >,
-        >,
-    >,
+    pub store: Arc<Mutex<Store>>,
     pub port: u16,
     pub memory_handler: Arc<Mutex<memory_handling::memory_handling::MemoryHandler>>,
     pub enable_log: bool,
     pub creds_manager: Arc<Mutex<CredsManager>>,
     pub persistent: bool,
+    pub version_seq: Arc<AtomicU64>,
+    pub locks: Arc<Mutex<HashMap<String, LockEntry>>>,
+    pub rate_limits: Arc<Mutex<HashMap<(String, String), RateLimitState>>>,
+    pub scripts: Arc<Mutex<ScriptCache>>,
+    pub script_limits: ScriptLimits,
+    pub job_seq: Arc<AtomicU64>,
+    pub delete_jobs: Arc<Mutex<HashMap<u64, DeleteByPatternProgress>>>,
+    pub aliases: Arc<Mutex<BTreeMap<String, String>>>,
+    pub cluster_configs: Arc<Mutex<HashMap<String, ClusterConfig>>>,
+    pub cluster_expirations: Arc<Mutex<HashMap<String, Instant>>>,
+    pub tag_index: Arc<Mutex<TagIndex>>,
 }
 
 impl Cache {
@@ -74,169 +208,559 @@ impl Cache {
         enable_logs: bool,
         persistent: bool,
         creds: Arc<Mutex<CredsManager>>,
+        script_limits: ScriptLimits,
     ) -> Self {
-        let mut cache = Cache {
-            store: Arc::new(Mutex::new(HashMap::new())),
+        Cache {
+            store: Arc::new(Mutex::new(BTreeMap::new())),
             port: port_number,
             memory_handler,
             evict_type,
             enable_log: enable_logs,
             persistent,
             creds_manager: creds,
-        };
-
-        if persistent {
-            cache.initialize_from_commands();
+            version_seq: Arc::new(AtomicU64::new(0)),
+            locks: Arc::new(Mutex::new(HashMap::new())),
+            rate_limits: Arc::new(Mutex::new(HashMap::new())),
+            scripts: Arc::new(Mutex::new(HashMap::new())),
+            script_limits,
+            job_seq: Arc::new(AtomicU64::new(0)),
+            delete_jobs: Arc::new(Mutex::new(HashMap::new())),
+            aliases: Arc::new(Mutex::new(BTreeMap::new())),
+            cluster_configs: Arc::new(Mutex::new(HashMap::new())),
+            cluster_expirations: Arc::new(Mutex::new(HashMap::new())),
+            tag_index: Arc::new(Mutex::new(HashMap::new())),
         }
-
-        cache
     }
 
-    pub fn initialize_from_commands(&mut self) {
-        let now: DateTime<Local> = Local::now();
-        let kn_dirs = &KNOWN_DIRECTORIES;
+    // rebuilds the cache from the latest snapshot and the persistence log after it.
+    // an error means the log can not be replayed past some record, serving the part before it
+    // and appending new writes behind it would lose those writes on the next start
+    pub fn initialize_from_commands(&mut self) -> Result<(), String> {
+        let mut summary = RecoverySummary::default();
 
-        let persistent_file_name = format!(
-            "{}/persistent_{}.qbx",
-            &kn_dirs.persistent_directory.display(),
-            now.format("%d-%m-%Y")
-        );
-
-        let open_file_result = OpenOptions::new().read(true).open(PathBuf::from(
-            &kn_dirs.app_root_directory.join(persistent_file_name),
-        ));
-
-        if let Ok(file) = open_file_result {
-            let reader = BufReader::new(file);
+        // the latest snapshot already holds everything logged before its position
+        let snapshot_state = snapshot::load_latest_snapshot();
+        let mut replay_from: Option<LogPosition> = snapshot_state
+            .as_ref()
+            .map(|state| state.log_position.clone());
 
-            for line in reader.lines() {
-                if let Ok(command) = line {
-                    let trimmed_command = command.trim_matches(|c| c == '\"');
-                    let _ = self.execute_command(&trimmed_command);
+        // files of the old text format are converted first, the snapshot offset moves along with its file
+        for (_, path) in persistent_Manager::persistent_files() {
+            let snapshot_offset = replay_from
+                .as_ref()
+                .filter(|position| {
+                    path.file_name()
+                        .map_or(false, |name| name == position.file.as_str())
+                })
+                .map(|position| position.offset);
+            match persistent_Manager::migrate_persistent_file(&path, snapshot_offset) {
+                Ok(Some(migration)) => {
+                    summary.migrated += 1;
+                    summary.skipped += migration.skipped;
+                    if let (Some(position), Some(offset)) = (replay_from.as_mut(), migration.offset)
+                    {
+                        position.offset = offset;
+                    }
                     let message = format!(
-                        "from persistent => command:{:?} executed successfully",
-                        &command
+                        "persistent file {:?} migrated to the current record format with {} commands",
+                        path, migration.records
                     );
-                    let log = Logger::log_info_data(&message);
-                    log.write_log_to_file();
+                    Logger::log_info_data(&message).write_log_to_file();
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    let message = format!("can not migrate persistent file {:?}: {}", path, error);
+                    Logger::log_error_data(&message).write_log_to_file();
+                }
+            }
+        }
+
+        if let Some(mut state) = snapshot_state {
+            // a migration moves the records, the snapshot has to point at the same record in the new layout
+            if let Some(position) = replay_from
+                .as_ref()
+                .filter(|position| position.offset != state.log_position.offset)
+            {
+                state.log_position = position.clone();
+                match snapshot::write_snapshot(&state) {
+                    Ok(path) => snapshot::remove_snapshots_except(&path),
+                    Err(error) => {
+                        let message = format!(
+                            "can not move the snapshot to the migrated log position: {}",
+                            error
+                        );
+                        Logger::log_error_data(&message).write_log_to_file();
+                    }
                 }
             }
-        } else {
+            let message = format!(
+                "snapshot from {} loaded, replaying log after {}:{}",
+                state.created_at, state.log_position.file, state.log_position.offset
+            );
+            self.restore_snapshot(state);
+            summary.snapshot_loaded = true;
+            Logger::log_info_data(&message).write_log_to_file();
+        }
+
+        // files are named by day, so replaying them oldest first keeps the original write order
+        let snapshot_file = replay_from
+            .as_ref()
+            .and_then(|position| persistent_Manager::persistent_file_date(&position.file));
+        for (date, path) in persistent_Manager::persistent_files() {
+            let offset = match (&replay_from, snapshot_file) {
+                (Some(position), Some(snapshot_date)) => {
+                    if date < snapshot_date {
+                        continue;
+                    }
+                    if date == snapshot_date {
+                        position.offset
+                    } else {
+                        0
+                    }
+                }
+                _ => 0,
+            };
+            self.replay_persistent_file(&path, offset, None, &mut summary)?;
+        }
+
+        if summary.files == 0 && !summary.snapshot_loaded {
             let log =
                 Logger::log_warn("No persistent command file found, starting with empty cache.");
             log.write_log_to_file();
         }
+        let message = format!(
+            "recovery finished => snapshot loaded:{} files:{} migrated:{} commands applied:{} commands skipped:{} torn records truncated:{}",
+            summary.snapshot_loaded,
+            summary.files,
+            summary.migrated,
+            summary.applied,
+            summary.skipped,
+            summary.truncated
+        );
+        println!("{}", message);
+        Logger::log_info_data(&message).write_log_to_file();
+        Ok(())
     }
 
-    fn execute_command(&mut self, command: &str) {
-        let parts: Vec<&str> = command.split_whitespace().collect();
-        if parts.is_empty() {
-            return;
+    // rebuilds the cache as it was at `until` (unix milliseconds) from the whole persistence history,
+    // meant for an empty cache that is not persisted itself. ttls still count from now,
+    // so keys that have expired since that point are not brought back.
+    // a point older than the history left on disk is refused rather than rebuilt from part of it
+    pub fn replay_until(&mut self, until: u64) -> Result<RecoverySummary, String> {
+        if let Some(history_start) = persistent_Manager::history_start() {
+            if until < history_start {
+                return Err(format!(
+                    "the persistence history only goes back to {}, {} can not be recovered",
+                    history_start, until
+                ));
+            }
         }
-        //println!("{:?}",parts[3].as_bytes().to_vec());
-        match parts[0] {
-            "SET" => {
-                let cleaned_input = command
-                    .trim_start_matches('[')
-                    .trim_end_matches(']')
-                    .trim_start_matches('"')
-                    .trim_end_matches('"');
-                let (before_bracket, after_bracket) =
-                    cleaned_input.split_once('[').unwrap_or((cleaned_input, ""));
-                let (inside_bracket, after_closing_bracket) =
-                    after_bracket.split_once(']').unwrap_or((after_bracket, ""));
-                let inside_bracket_cleaned = inside_bracket.replace(" ", "");
-                let set_command = format!(
-                    "{}[{}]{}",
-                    before_bracket, inside_bracket_cleaned, after_closing_bracket
-                );
+        let mut summary = RecoverySummary::default();
+        let until_day = i64::try_from(until)
+            .ok()
+            .and_then(|until| Local.timestamp_millis_opt(until).earliest())
+            .map(|until| until.date_naive());
+        for (date, path) in persistent_Manager::persistent_files() {
+            if until_day.map_or(false, |until_day| date > until_day) {
+                continue;
+            }
+            self.replay_persistent_file(&path, 0, Some(until), &mut summary)?;
+        }
+        let message = format!(
+            "point in time replay until {} => files:{} commands applied:{} commands skipped:{} commands after target:{}",
+            until, summary.files, summary.applied, summary.skipped, summary.after_target
+        );
+        Logger::log_info_data(&message).write_log_to_file();
+        Ok(summary)
+    }
 
-                let splited_command: Vec<&str> = set_command.split_whitespace().collect();
-                if splited_command.len() == 4 {
-                    let trimmed_input = splited_command[3].trim_matches(|c| c == '[' || c == ']');
-                    let str_numbers = trimmed_input.split(",");
-                    let vec_u8: Vec<u8> = str_numbers
-                        .map(|s| s.parse().expect("Invalid byte"))
-                        .collect();
-                    let cluster = splited_command[1].to_string();
-                    let key = splited_command[2].to_string();
-                    self.set(cluster, key, vec_u8, None, true);
-                }
-            }
-            "INCR" => {
-                let cleaned_input = command
-                    .trim_start_matches('[')
-                    .trim_end_matches(']')
-                    .trim_start_matches('"')
-                    .trim_end_matches('"');
-                let (before_bracket, after_bracket) =
-                    cleaned_input.split_once('[').unwrap_or((cleaned_input, ""));
-                let (inside_bracket, after_closing_bracket) =
-                    after_bracket.split_once(']').unwrap_or((after_bracket, ""));
-                let inside_bracket_cleaned = inside_bracket.replace(" ", "");
-                let set_command = format!(
-                    "{}[{}]{}",
-                    before_bracket, inside_bracket_cleaned, after_closing_bracket
+    // replays the records of a persistent file starting at a byte offset,
+    // a record cut short at the end of the file is truncated away so new writes follow a valid one.
+    // with `until` only records written up to then are applied and the file is left as it is.
+    // a damaged frame header or a record that can not be read, like one sealed with a key that is
+    // not configured, stops the replay with an error and the file is left as it is
+    fn replay_persistent_file(
+        &mut self,
+        path: &PathBuf,
+        offset: u64,
+        until: Option<u64>,
+        summary: &mut RecoverySummary,
+    ) -> Result<(), String> {
+        let Ok(mut file) = OpenOptions::new()
+            .read(true)
+            .write(until.is_none())
+            .open(path)
+        else {
+            return Ok(());
+        };
+        match record::read_header(&mut file) {
+            Ok(Some(record::LOG_VERSION)) => {}
+            Ok(Some(version)) => {
+                let message = format!(
+                    "persistent file {:?} has record version {} that was not migrated, skipped",
+                    path, version
                 );
+                Logger::log_warn_data(&message).write_log_to_file();
+                return Ok(());
+            }
+            Ok(None) => {
+                let message = format!("persistent file {:?} has no record header, skipped", path);
+                Logger::log_warn_data(&message).write_log_to_file();
+                return Ok(());
+            }
+            Err(error) => {
+                let message = format!("can not read persistent file {:?}: {}", path, error);
+                Logger::log_error_data(&message).write_log_to_file();
+                return Ok(());
+            }
+        }
+        let offset = offset.max(record::LOG_HEADER_LEN);
+        let file_len = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        if file.seek(SeekFrom::Start(offset)).is_err() {
+            return Ok(());
+        }
+        summary.files += 1;
 
-                let splited_command: Vec<&str> = set_command.split_whitespace().collect();
-                if splited_command.len() == 4 {
-                    let trimmed_input = splited_command[3].trim_matches(|c| c == '[' || c == ']');
-                    let str_numbers = trimmed_input.split(",");
-                    let vec_u8: Vec<u8> = str_numbers
-                        .map(|s| s.parse().expect("Invalid byte"))
-                        .collect();
-                    let main_value = vec_to_i32(vec_u8);
-                    let cluster = splited_command[1].to_string();
-                    let key = splited_command[2].to_string();
-                    self.incr(cluster, key, main_value, true);
-                }
-            }
-            "DECR" => {
-                let cleaned_input = command
-                    .trim_start_matches('[')
-                    .trim_end_matches(']')
-                    .trim_start_matches('"')
-                    .trim_end_matches('"');
-                let (before_bracket, after_bracket) =
-                    cleaned_input.split_once('[').unwrap_or((cleaned_input, ""));
-                let (inside_bracket, after_closing_bracket) =
-                    after_bracket.split_once(']').unwrap_or((after_bracket, ""));
-                let inside_bracket_cleaned = inside_bracket.replace(" ", "");
-                let set_command = format!(
-                    "{}[{}]{}",
-                    before_bracket, inside_bracket_cleaned, after_closing_bracket
-                );
+        let mut reader = RecordReader::new(BufReader::new(&file), offset);
+        loop {
+            let record_start = reader.offset;
+            match reader.next_record() {
+                Ok(ReadRecord::Record(record))
+                    if until.map_or(false, |until| record.timestamp() > until) =>
+                {
+                    summary.after_target += 1;
+                }
+                Ok(ReadRecord::Record(record)) => {
+                    if self.execute_command(&record) {
+                        summary.applied += 1;
+                        let message = format!(
+                            "from persistent => command:{} executed successfully",
+                            record.command()
+                        );
+                        Logger::log_info_data(&message).write_log_to_file();
+                    } else {
+                        summary.skipped += 1;
+                        let message =
+                            format!("from persistent => command:{} skipped", record.command());
+                        Logger::log_warn_data(&message).write_log_to_file();
+                    }
+                }
+                Ok(ReadRecord::Corrupt) if reader.offset < file_len => {
+                    summary.skipped += 1;
+                    let message = format!("corrupt record at {:?}:{} skipped", path, record_start);
+                    Logger::log_warn_data(&message).write_log_to_file();
+                }
+                // a record still being written is not torn, it is just not complete yet
+                Ok(ReadRecord::Corrupt) | Ok(ReadRecord::Torn) if until.is_some() => break,
+                // the length of the frame is covered by its header crc, so a frame running past the end is the last one
+                Ok(ReadRecord::Corrupt) | Ok(ReadRecord::Torn) => {
+                    summary.truncated += 1;
+                    let message = format!(
+                        "torn record at the end of {:?} truncated at {}",
+                        path, record_start
+                    );
+                    Logger::log_warn_data(&message).write_log_to_file();
+                    if let Err(error) = file.set_len(record_start) {
+                        let message = format!("can not truncate {:?}: {}", path, error);
+                        Logger::log_error_data(&message).write_log_to_file();
+                    }
+                    break;
+                }
+                Ok(ReadRecord::Damaged) => {
+                    let message = format!(
+                        "damaged frame header at {:?}:{}, replay stopped and the file left as it is",
+                        path, record_start
+                    );
+                    Logger::log_error_data(&message).write_log_to_file();
+                    return Err(message);
+                }
+                Ok(ReadRecord::End) => break,
+                Err(error) => {
+                    let message = format!(
+                        "can not read persistent file {:?} at {}: {}",
+                        path, record_start, error
+                    );
+                    Logger::log_error_data(&message).write_log_to_file();
+                    return Err(message);
+                }
+            }
+        }
+        Ok(())
+    }
 
-                let splited_command: Vec<&str> = set_command.split_whitespace().collect();
-                if splited_command.len() == 4 {
-                    let trimmed_input = splited_command[3].trim_matches(|c| c == '[' || c == ']');
-                    let str_numbers = trimmed_input.split(",");
-                    let vec_u8: Vec<u8> = str_numbers
-                        .map(|s| s.parse().expect("Invalid byte"))
-                        .collect();
-                    let main_value = vec_to_i32(vec_u8);
-                    let cluster = splited_command[1].to_string();
-                    let key = splited_command[2].to_string();
-                    self.decr(cluster, key, main_value, true);
-                }
-            }
-            "DEL" => {
-                if parts.len() == 3 {
-                    let cluster = parts[1].to_string();
-                    let key = parts[2].to_string();
-                    self.delete(&cluster.as_str(), &key.as_str(), true)
-                }
-            }
-            "CLEAR_CLUSTER" => {
-                if parts.len() == 2 {
-                    let cluster = parts[1];
+    // time left until a live key expires, None when there is no such key and Some(None) when it does not expire
+    fn live_ttl(&self, cluster: &str, key: &str) -> Option<Option<Duration>> {
+        let cluster = self.resolve_cluster(cluster);
+        let now = Instant::now();
+        let store = self.store.lock().unwrap();
+        let (_, expiration, _, _, _, _) = store
+            .get(&cluster)?
+            .get(key)
+            .filter(|entry| is_live(entry, now))?;
+        Some(expiration.map(|expiration| expiration.saturating_duration_since(now)))
+    }
+
+    // applies one persisted command, tells whether it was understood and replayed
+    fn execute_command(&mut self, record: &PersistentRecord) -> bool {
+        let arg = |index: usize| record.str_arg(index);
+        let value = |index: usize| record.bytes_arg(index).map(|value| value.to_vec());
+        match (record.command(), record.len()) {
+            // a SET without a deadline had no ttl, the default ttl of the cluster was already part of the record
+            ("SET", 3) => {
+                if let (Some(cluster), Some(key), Some(value)) = (arg(0), arg(1), value(2)) {
+                    return self.set_exact(cluster, key, value, None, true);
+                }
+            }
+            ("SET", 4) => {
+                let deadline = arg(3).and_then(|deadline| deadline.parse::<u64>().ok());
+                if let (Some(cluster), Some(key), Some(value), Some(deadline)) =
+                    (arg(0), arg(1), value(2), deadline)
+                {
+                    // a key whose ttl ran out while the server was down is gone, not set again
+                    return match record::remaining_ttl(deadline) {
+                        Some(ttl) => {
+                            self.set(cluster.to_string(), key.to_string(), value, Some(ttl), true)
+                        }
+                        None => {
+                            self.delete(cluster, key, true);
+                            true
+                        }
+                    };
+                }
+            }
+            ("SET_CLUSTER", 1) => {
+                if let Some(cluster) = arg(0) {
+                    self.set_cluster(cluster.to_string(), true);
+                    return true;
+                }
+            }
+            ("EXPIRE", 3) => {
+                let deadline = arg(2).and_then(|deadline| deadline.parse::<u64>().ok());
+                if let (Some(cluster), Some(key), Some(deadline)) = (arg(0), arg(1), deadline) {
+                    match record::remaining_ttl(deadline) {
+                        Some(ttl) => {
+                            self.expire_key(
+                                &cluster.to_string(),
+                                &key.to_string(),
+                                &(ttl.as_millis() as u64),
+                                true,
+                            );
+                        }
+                        None => {
+                            self.delete(cluster, key, true);
+                        }
+                    }
+                    return true;
+                }
+            }
+            // INCR and DECR records of older files hold the value the counter ended up at as well
+            ("SET_INT", 3) => {
+                let value = value(2).and_then(vec_to_i32);
+                if let (Some(cluster), Some(key), Some(value)) = (arg(0), arg(1), value) {
+                    return self.set_int(cluster, key, value, None, true);
+                }
+            }
+            // older INCR and DECR records hold the resulting value without a deadline,
+            // the counter keeps the expiration it had or gets the default ttl of its cluster like INCR did
+            ("INCR", 3) | ("DECR", 3) => {
+                let value = value(2).and_then(vec_to_i32);
+                if let (Some(cluster), Some(key), Some(value)) = (arg(0), arg(1), value) {
+                    let ttl = self.live_ttl(cluster, key).unwrap_or_else(|| {
+                        self.cluster_default_ttl(&self.resolve_cluster(cluster))
+                    });
+                    return self.set_int(cluster, key, value, ttl, true);
+                }
+            }
+            ("SET_INT", 4) => {
+                let value = value(2).and_then(vec_to_i32);
+                let deadline = arg(3).and_then(|deadline| deadline.parse::<u64>().ok());
+                if let (Some(cluster), Some(key), Some(value), Some(deadline)) =
+                    (arg(0), arg(1), value, deadline)
+                {
+                    // a counter whose ttl ran out while the server was down is gone, not set again
+                    return match record::remaining_ttl(deadline) {
+                        Some(ttl) => self.set_int(cluster, key, value, Some(ttl), true),
+                        None => {
+                            self.delete(cluster, key, true);
+                            true
+                        }
+                    };
+                }
+            }
+            ("DEL", 2) => {
+                if let (Some(cluster), Some(key)) = (arg(0), arg(1)) {
+                    self.delete(cluster, key, true);
+                    return true;
+                }
+            }
+            ("CLEAR_CLUSTER", 1) => {
+                if let Some(cluster) = arg(0) {
                     self.clear_cluster(cluster, true);
+                    return true;
                 }
             }
-            "CLEAR_ALL" => self.clear_all(true),
-            _ => println!("Unknown command: {}", command),
+            ("APPEND", 3) => {
+                if let (Some(cluster), Some(key), Some(value)) = (arg(0), arg(1), value(2)) {
+                    return self.append(cluster, key, &value, true).is_ok();
+                }
+            }
+            ("SETRANGE", 4) => {
+                let offset = arg(2).and_then(|offset| offset.parse::<usize>().ok());
+                if let (Some(cluster), Some(key), Some(offset), Some(value)) =
+                    (arg(0), arg(1), offset, value(3))
+                {
+                    return self.set_range(cluster, key, offset, &value, true).is_ok();
+                }
+            }
+            ("RENAME", 3) => {
+                if let (Some(cluster), Some(key), Some(new_key)) = (arg(0), arg(1), arg(2)) {
+                    return matches!(
+                        self.rename_key(cluster, key, new_key, false, true),
+                        Ok(true)
+                    );
+                }
+            }
+            ("COPY", 4) => {
+                if let (Some(src), Some(key), Some(desc), Some(desc_key)) =
+                    (arg(0), arg(1), arg(2), arg(3))
+                {
+                    return matches!(
+                        self.copy_key(src, key, desc, desc_key, true, true),
+                        Ok(true)
+                    );
+                }
+            }
+            ("MOVE", 3) => {
+                if let (Some(src), Some(key), Some(desc)) = (arg(0), arg(1), arg(2)) {
+                    return matches!(self.move_key(src, key, desc, true, true), Ok(true));
+                }
+            }
+            ("COPY_CLUSTER", 3) | ("MOVE_CLUSTER", 3) => {
+                let policy = arg(2).and_then(|policy| policy.parse::<ClusterWritePolicy>().ok());
+                if let (Some(src), Some(desc), Some(policy)) = (arg(0), arg(1), policy) {
+                    let result = if record.command() == "COPY_CLUSTER" {
+                        self.copy_cluster(src, desc, policy, true)
+                    } else {
+                        self.move_cluster(src, desc, policy, true)
+                    };
+                    return result.is_ok();
+                }
+            }
+            ("CLUSTER_CONFIG", 2) => {
+                let config =
+                    arg(1).and_then(|config| serde_json::from_str::<ClusterConfig>(config).ok());
+                if let (Some(cluster), Some(config)) = (arg(0), config) {
+                    return self.set_cluster_config(cluster, config, true).is_ok();
+                }
+            }
+            ("TAG", count) if count >= 3 => {
+                let tags: Option<Vec<String>> = (2..count)
+                    .map(|index| arg(index).map(String::from))
+                    .collect();
+                if let (Some(cluster), Some(key), Some(tags)) = (arg(0), arg(1), tags) {
+                    return self.tag_key(cluster, key, &tags, true).is_ok();
+                }
+            }
+            ("INVALIDATE_TAG", 1) => {
+                if let Some(tag) = arg(0) {
+                    self.invalidate_tag(tag, None, true);
+                    return true;
+                }
+            }
+            ("INVALIDATE_TAG", 2) => {
+                let deadline = arg(1).and_then(|deadline| deadline.parse::<u64>().ok());
+                if let (Some(tag), Some(deadline)) = (arg(0), deadline) {
+                    self.invalidate_tag(tag, record::remaining_ttl(deadline), true);
+                    return true;
+                }
+            }
+            ("DROP_CLUSTER", 1) => {
+                if let Some(cluster) = arg(0) {
+                    self.drop_cluster(cluster, true);
+                    return true;
+                }
+            }
+            ("EXPIRE_CLUSTER", 2) => {
+                let deadline = arg(1).and_then(|deadline| deadline.parse::<u64>().ok());
+                if let (Some(cluster), Some(deadline)) = (arg(0), deadline) {
+                    match record::remaining_ttl(deadline) {
+                        Some(ttl) => {
+                            self.expire_cluster(cluster, Some(ttl), true);
+                        }
+                        None => {
+                            self.drop_cluster(cluster, true);
+                        }
+                    }
+                    return true;
+                }
+            }
+            ("PERSIST_CLUSTER", 1) => {
+                if let Some(cluster) = arg(0) {
+                    self.expire_cluster(cluster, None, true);
+                    return true;
+                }
+            }
+            ("ALIAS", 3) => {
+                if let (Some(alias), Some(target), Some(drop_previous)) = (arg(0), arg(1), arg(2)) {
+                    return self
+                        .set_alias(alias, target, drop_previous == "true", true)
+                        .is_ok();
+                }
+            }
+            ("UNALIAS", 1) => {
+                if let Some(alias) = arg(0) {
+                    self.remove_alias(alias, true);
+                    return true;
+                }
+            }
+            ("CLEAR_ALL", 0) => {
+                self.clear_all(true);
+                return true;
+            }
+            _ => println!("Unknown command: {}", record.command()),
         }
+        false
+    }
+
+    // Every write stamps its entry with a new version taken from a cache-wide sequence,
+    // so a key that is deleted and set again never reuses a version a client has seen.
+    pub fn next_version(&self) -> u64 {
+        self.version_seq.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    // every mutation reaches the persistence log through here, whether logging is enabled or not
+    pub fn persist(&self, record: PersistentRecord, ignore_persistent: bool) {
+        if !self.persistent || ignore_persistent {
+            return;
+        }
+        if let Err(error) = persistent_Manager::write_to_persistent_file(&record) {
+            let message = format!(
+                "command:{} could not be persisted: {}",
+                record.command(),
+                error
+            );
+            Logger::log_error_data(&message).write_log_to_file();
+        }
+    }
+
+    // evicts when the memory limit is reached and tells whether a write to `cluster` still fits
+    pub fn has_memory_for_write(&self, cluster: &str, ignore_persistent: bool) -> bool {
+        if self
+            .memory_handler
+            .lock()
+            .unwrap()
+            .is_memory_limit_finished()
+        {
+            self.evict_entries(cluster, ignore_persistent);
+            if self.enable_log {
+                Logger::log_warn("Memory limit exceeded. Evicting entries").write_log_to_file();
+            }
+        }
+        !self
+            .memory_handler
+            .lock()
+            .unwrap()
+            .is_memory_limit_finished()
     }
 
     pub fn configure_default_port(&mut self, port: u16) {
@@ -248,37 +772,76 @@ impl Cache {
     }
 
     // Eviction strategies
-    pub fn evict_entries(&self) {
+    // a cluster with its own strategy makes room for its writes from its own entries first,
+    // anything else is evicted across all clusters with the global strategy
+    pub fn evict_entries(&self, cluster: &str, ignore_persistent: bool) {
         let mut store = self.store.lock().unwrap();
         let mut memory_handler = self.memory_handler.lock().unwrap();
-        let eviction_strategy = EvictionStrategy::from_i32(self.evict_type);
+        let cluster_strategy = self
+            .cluster_configs
+            .lock()
+            .unwrap()
+            .get(cluster)
+            .and_then(|config| config.eviction_strategy);
+        if let Some(evict_type) = cluster_strategy {
+            if self.evict_with_strategy(
+                &mut store,
+                &mut memory_handler,
+                evict_type,
+                Some(cluster),
+                ignore_persistent,
+            ) {
+                return;
+            }
+        }
+        self.evict_with_strategy(
+            &mut store,
+            &mut memory_handler,
+            self.evict_type,
+            None,
+            ignore_persistent,
+        );
+    }
+
+    // evicts a single entry, only looking at `cluster` when one is given; tells whether anything was evicted.
+    // the eviction is persisted as a DEL, a replay deletes the same entry instead of picking one again
+    pub fn evict_with_strategy(
+        &self,
+        store: &mut Store,
+        memory_handler: &mut memory_handling::memory_handling::MemoryHandler,
+        evict_type: i32,
+        cluster: Option<&str>,
+        ignore_persistent: bool,
+    ) -> bool {
+        let eviction_strategy = EvictionStrategy::from_i32(evict_type);
         match eviction_strategy.unwrap() {
             EvictionStrategy::VolatileLru => {
-                self.evict_volatile_lru(&mut store, &mut memory_handler)
+                self.evict_volatile_lru(store, memory_handler, cluster, ignore_persistent)
+            }
+            EvictionStrategy::AllKeysLru => {
+                self.evict_allkeys_lru(store, memory_handler, cluster, ignore_persistent)
             }
-            EvictionStrategy::AllKeysLru => self.evict_allkeys_lru(&mut store, &mut memory_handler),
             EvictionStrategy::AllKeysRandom => {
-                self.evict_allkeys_random(&mut store, &mut memory_handler)
+                self.evict_allkeys_random(store, memory_handler, cluster, ignore_persistent)
             }
             EvictionStrategy::VolatileTtl => {
-                self.evict_volatile_ttl(&mut store, &mut memory_handler)
+                self.evict_volatile_ttl(store, memory_handler, cluster, ignore_persistent)
             }
         }
     }
 
     fn evict_volatile_lru(
         &self,
-        store: &mut HashMap<
-            String,
-            HashMap<String, (Vec<u8>, Option<Instant>, Option<Duration>, CacheType)>,
-        >,
+        store: &mut Store,
         memory_handler: &mut memory_handling::memory_handling::MemoryHandler,
-    ) {
+        cluster: Option<&str>,
+        ignore_persistent: bool,
+    ) -> bool {
         let mut lru_key: Option<(String, String)> = None;
 
         // Find the least recently used key among those with an expiration set
-        for (cluster_key, cluster_store) in store.iter() {
-            for (key, (_, expiration_time, _, _)) in cluster_store {
+        for (cluster_key, cluster_store) in in_scope(store, cluster) {
+            for (key, (_, expiration_time, _, _, _, _)) in cluster_store {
                 if let Some(exp) = expiration_time {
                     if exp > &Instant::now() {
                         if lru_key.is_none() {
@@ -292,31 +855,39 @@ impl Cache {
         // Evict the LRU key
         if let Some((cluster_key, key_to_evict)) = lru_key {
             if let Some(cluster_store) = store.get_mut(&cluster_key) {
-                if let Some((value, _, _, _)) = cluster_store.remove(&key_to_evict) {
-                    let memory_usage = std::mem::size_of_val(&value);
+                if let Some(entry) = cluster_store.remove(&key_to_evict) {
+                    self.unindex_entry_tags(&cluster_key, &key_to_evict, &entry);
+                    let memory_usage = std::mem::size_of_val(&entry.0);
                     memory_handler.delete_memory(memory_usage);
+                    self.persist(
+                        PersistentRecord::new("DEL")
+                            .arg(&cluster_key)
+                            .arg(&key_to_evict),
+                        ignore_persistent,
+                    );
                     println!(
                         "Evicted [{}] from cluster [{}] using volatile LRU strategy",
                         key_to_evict, cluster_key
                     );
+                    return true;
                 }
             }
         }
+        false
     }
 
     fn evict_volatile_ttl(
         &self,
-        store: &mut HashMap<
-            String,
-            HashMap<String, (Vec<u8>, Option<Instant>, Option<Duration>, CacheType)>,
-        >,
+        store: &mut Store,
         memory_handler: &mut memory_handling::memory_handling::MemoryHandler,
-    ) {
+        cluster: Option<&str>,
+        ignore_persistent: bool,
+    ) -> bool {
         let mut shortest_ttl_key: Option<(String, String, Instant)> = None;
 
         // Find the key with the shortest TTL among those with an expiration set
-        for (cluster_key, cluster_store) in store.iter() {
-            for (key, (_, expiration_time, _, _)) in cluster_store {
+        for (cluster_key, cluster_store) in in_scope(store, cluster) {
+            for (key, (_, expiration_time, _, _, _, _)) in cluster_store {
                 if let Some(exp) = expiration_time {
                     if exp > &Instant::now() {
                         if shortest_ttl_key.is_none() {
@@ -330,31 +901,39 @@ impl Cache {
         // Evict the key with the shortest TTL
         if let Some((cluster_key, key_to_evict, _)) = shortest_ttl_key {
             if let Some(cluster_store) = store.get_mut(&cluster_key) {
-                if let Some((value, _, _, _)) = cluster_store.remove(&key_to_evict) {
-                    let memory_usage = std::mem::size_of_val(&value);
+                if let Some(entry) = cluster_store.remove(&key_to_evict) {
+                    self.unindex_entry_tags(&cluster_key, &key_to_evict, &entry);
+                    let memory_usage = std::mem::size_of_val(&entry.0);
                     memory_handler.delete_memory(memory_usage);
+                    self.persist(
+                        PersistentRecord::new("DEL")
+                            .arg(&cluster_key)
+                            .arg(&key_to_evict),
+                        ignore_persistent,
+                    );
                     println!(
                         "Evicted [{}] from cluster [{}] using volatile TTL strategy",
                         key_to_evict, cluster_key
                     );
+                    return true;
                 }
             }
         }
+        false
     }
 
     fn evict_allkeys_lru(
         &self,
-        store: &mut HashMap<
-            String,
-            HashMap<String, (Vec<u8>, Option<Instant>, Option<Duration>, CacheType)>,
-        >,
+        store: &mut Store,
         memory_handler: &mut memory_handling::memory_handling::MemoryHandler,
-    ) {
+        cluster: Option<&str>,
+        ignore_persistent: bool,
+    ) -> bool {
         let mut lru_key: Option<(String, String)> = None;
 
         // Find the least recently used key regardless of expiration
-        for (cluster_key, cluster_store) in store.iter() {
-            for (key, (_, _expiration_time, _, _)) in cluster_store {
+        for (cluster_key, cluster_store) in in_scope(store, cluster) {
+            for (key, (_, _expiration_time, _, _, _, _)) in cluster_store {
                 if lru_key.is_none() {
                     lru_key = Some((cluster_key.clone(), key.clone()));
                 }
@@ -364,53 +943,79 @@ impl Cache {
         // Evict the LRU key
         if let Some((cluster_key, key_to_evict)) = lru_key {
             if let Some(cluster_store) = store.get_mut(&cluster_key) {
-                if let Some((value, _, _, _)) = cluster_store.remove(&key_to_evict) {
-                    let memory_usage = std::mem::size_of_val(&value);
+                if let Some(entry) = cluster_store.remove(&key_to_evict) {
+                    self.unindex_entry_tags(&cluster_key, &key_to_evict, &entry);
+                    let memory_usage = std::mem::size_of_val(&entry.0);
                     memory_handler.delete_memory(memory_usage);
+                    self.persist(
+                        PersistentRecord::new("DEL")
+                            .arg(&cluster_key)
+                            .arg(&key_to_evict),
+                        ignore_persistent,
+                    );
                     println!(
                         "Evicted [{}] from cluster [{}] using allkeys LRU strategy",
                         key_to_evict, cluster_key
                     );
+                    return true;
                 }
             }
         }
+        false
     }
 
     fn evict_allkeys_random(
         &self,
-        store: &mut HashMap<
-            String,
-            HashMap<String, (Vec<u8>, Option<Instant>, Option<Duration>, CacheType)>,
-        >,
+        store: &mut Store,
         memory_handler: &mut memory_handling::memory_handling::MemoryHandler,
-    ) {
-        let keys: Vec<(String, String)> = store
-            .iter()
+        cluster: Option<&str>,
+        ignore_persistent: bool,
+    ) -> bool {
+        let keys: Vec<(String, String)> = in_scope(store, cluster)
             .flat_map(|(cluster_key, cluster_store)| {
                 cluster_store
                     .iter()
-                    .map(|(key, (_value, _, _, _))| (cluster_key.clone(), key.clone()))
+                    .map(|(key, (_value, _, _, _, _, _))| (cluster_key.clone(), key.clone()))
                     .collect::<Vec<_>>()
             })
             .collect();
 
         if let Some((cluster_key, key_to_evict)) = keys.choose(&mut rand::thread_rng()) {
             if let Some(cluster_store) = store.get_mut(cluster_key) {
-                if let Some((value, _, _, _)) = cluster_store.remove(key_to_evict) {
-                    let memory_usage = std::mem::size_of_val(&value);
+                if let Some(entry) = cluster_store.remove(key_to_evict) {
+                    self.unindex_entry_tags(cluster_key, key_to_evict, &entry);
+                    let memory_usage = std::mem::size_of_val(&entry.0);
                     memory_handler.delete_memory(memory_usage);
+                    self.persist(
+                        PersistentRecord::new("DEL")
+                            .arg(cluster_key)
+                            .arg(key_to_evict),
+                        ignore_persistent,
+                    );
                     println!(
                         "Evicted [{}] from cluster [{}] using allkeys random strategy",
                         key_to_evict, cluster_key
                     );
+                    return true;
                 }
             }
         }
+        false
     }
 }
 
+// clusters an eviction pass may pick from
+fn in_scope<'a>(
+    store: &'a Store,
+    cluster: Option<&'a str>,
+) -> impl Iterator<Item = (&'a String, &'a ClusterStore)> {
+    store
+        .iter()
+        .filter(move |(cluster_key, _)| cluster.map_or(true, |cluster| cluster == *cluster_key))
+}
+
 //strategy
-enum EvictionStrategy {
+pub enum EvictionStrategy {
     VolatileLru,
     VolatileTtl,
     AllKeysLru,
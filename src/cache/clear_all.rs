@@ -1,4 +1,4 @@
-use crate::persistent::persistent_Manager;
+use crate::persistent::record::PersistentRecord;
 
 use super::Cache;
 
@@ -13,13 +13,11 @@ impl ClearAll for Cache {
         let total_size: usize = store
             .values()
             .flat_map(|cluster_store| cluster_store.values())
-            .map(|(v, _, _, _)| v.len())
+            .map(|(v, _, _, _, _, _)| v.len())
             .sum();
         store.clear();
+        self.tag_index.lock().unwrap().clear();
         memory_handler.delete_memory(total_size);
-        if self.persistent && !ignore_persistent {
-            let command = format!("CLEAR_ALL");
-            persistent_Manager::write_to_persistent_file(&command);
-        }
+        self.persist(PersistentRecord::new("CLEAR_ALL"), ignore_persistent);
     }
 }
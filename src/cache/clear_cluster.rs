@@ -1,4 +1,4 @@
-use crate::{logger::logger_manager::Logger, persistent::persistent_Manager};
+use crate::{logger::logger_manager::Logger, persistent::record::PersistentRecord};
 
 use super::Cache;
 
@@ -8,22 +8,30 @@ pub trait ClearCluster {
 
 impl ClearCluster for Cache {
     fn clear_cluster(&self, cluster: &str, ignore_persistent: bool) {
+        let cluster = &self.resolve_cluster(cluster);
         let mut store = self.store.lock().unwrap();
+        if self
+            .check_cluster_write(&mut store, cluster, None, ignore_persistent)
+            .is_err()
+        {
+            return;
+        }
         if let Some(cluster_store) = store.remove(cluster) {
+            self.unindex_cluster_tags(cluster, &cluster_store);
             let mut memory_handler = self.memory_handler.lock().unwrap();
             let total_size: usize = cluster_store
                 .values()
-                .map(|(v, _, _, _)| std::mem::size_of_val(v))
+                .map(|(v, _, _, _, _, _)| std::mem::size_of_val(v))
                 .sum();
             memory_handler.delete_memory(total_size);
             if self.enable_log == true {
                 let clear_cluster_log = Logger::log_info("cluster cleared ");
                 clear_cluster_log.write_log_to_file();
-                if self.persistent && !ignore_persistent {
-                    let command = format!("CLEAR_CLUSTER {}", cluster.clone());
-                    persistent_Manager::write_to_persistent_file(&command);
-                }
             }
+            self.persist(
+                PersistentRecord::new("CLEAR_CLUSTER").arg(cluster),
+                ignore_persistent,
+            );
         }
     }
 }
@@ -1,6 +1,10 @@
+use std::ops::Bound;
 use std::time::Instant;
 
-use super::Cache;
+use super::{
+    cache::{is_live, ClusterStore},
+    Cache,
+};
 
 pub trait ClearExpired {
     fn clear_expired(&self);
@@ -10,14 +14,82 @@ impl ClearExpired for Cache {
     fn clear_expired(&self) {
         let mut store = self.store.lock().unwrap();
 
-        for cluster_store in store.values_mut() {
-            cluster_store.retain(|_, (_, expiration_time, _, _)| {
-                if let Some(exp) = expiration_time {
-                    exp > &mut Instant::now()
-                } else {
-                    true
+        for (cluster, cluster_store) in store.iter_mut() {
+            self.remove_expired(cluster, cluster_store);
+        }
+    }
+}
+
+impl Cache {
+    // drops expired entries of a cluster, keeping memory usage and the tag index in sync
+    pub fn remove_expired(&self, cluster: &str, cluster_store: &mut ClusterStore) {
+        let now = Instant::now();
+        let mut freed = 0;
+        cluster_store.retain(|key, entry| {
+            let live = is_live(entry, now);
+            if !live {
+                self.unindex_entry_tags(cluster, key, entry);
+                freed += std::mem::size_of_val(&entry.0);
+            }
+            live
+        });
+        if freed > 0 {
+            self.memory_handler.lock().unwrap().delete_memory(freed);
+        }
+    }
+
+    // looks at no more than `count` entries, continuing after the cluster and key of `cursor`,
+    // and drops the expired ones. returns where the next call continues, None once the sweep
+    // went through every cluster and starts over
+    pub fn clear_expired_batch(
+        &self,
+        cursor: Option<(String, String)>,
+        count: usize,
+    ) -> Option<(String, String)> {
+        let mut store = self.store.lock().unwrap();
+        let now = Instant::now();
+        let (first_cluster, after_key) = match cursor.as_ref() {
+            Some((cluster, key)) => (Bound::Included(cluster.as_str()), Some((cluster, key))),
+            None => (Bound::Unbounded, None),
+        };
+
+        let mut examined = 0;
+        let mut expired: Vec<(String, String)> = Vec::new();
+        let mut next = None;
+        'clusters: for (cluster, cluster_store) in
+            store.range::<str, _>((first_cluster, Bound::Unbounded))
+        {
+            let first_key = match after_key {
+                Some((after_cluster, key)) if after_cluster == cluster => {
+                    Bound::Excluded(key.as_str())
+                }
+                _ => Bound::Unbounded,
+            };
+            for (key, entry) in cluster_store.range::<str, _>((first_key, Bound::Unbounded)) {
+                if !is_live(entry, now) {
+                    expired.push((cluster.clone(), key.clone()));
+                }
+                examined += 1;
+                if examined == count {
+                    next = Some((cluster.clone(), key.clone()));
+                    break 'clusters;
                 }
-            });
+            }
+        }
+
+        let mut freed = 0;
+        for (cluster, key) in expired {
+            if let Some(entry) = store
+                .get_mut(&cluster)
+                .and_then(|cluster_store| cluster_store.remove(&key))
+            {
+                self.unindex_entry_tags(&cluster, &key, &entry);
+                freed += std::mem::size_of_val(&entry.0);
+            }
+        }
+        if freed > 0 {
+            self.memory_handler.lock().unwrap().delete_memory(freed);
         }
+        next
     }
 }
@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{logger::logger_manager::Logger, persistent::record::PersistentRecord};
+
+use super::{cache::CacheError, Cache};
+
+#[derive(Clone, Serialize, Debug)]
+pub struct AliasResult {
+    pub alias: String,
+    pub target: String,
+    pub previous_target: Option<String>,
+    pub dropped: bool,
+}
+
+pub trait ClusterAlias {
+    fn set_alias(
+        &mut self,
+        alias: &str,
+        target: &str,
+        drop_previous: bool,
+        ignore_persistent: bool,
+    ) -> Result<AliasResult, CacheError>;
+    fn remove_alias(&mut self, alias: &str, ignore_persistent: bool) -> bool;
+    fn get_aliases(&self) -> BTreeMap<String, String>;
+}
+
+impl ClusterAlias for Cache {
+    fn set_alias(
+        &mut self,
+        alias: &str,
+        target: &str,
+        drop_previous: bool,
+        ignore_persistent: bool,
+    ) -> Result<AliasResult, CacheError> {
+        // store and aliases stay locked together so readers never observe a half re-pointed alias
+        let mut store = self.store.lock().unwrap();
+        let mut aliases = self.aliases.lock().unwrap();
+
+        let target = aliases
+            .get(target)
+            .cloned()
+            .unwrap_or_else(|| target.to_string());
+        if target == alias || store.contains_key(alias) {
+            return Err(CacheError::AliasConflict);
+        }
+        if !store.contains_key(&target) {
+            return Err(CacheError::ClusterNotFound);
+        }
+
+        let previous_target = aliases.insert(alias.to_string(), target.clone());
+        let mut dropped = false;
+        if let Some(previous) = previous_target.as_ref() {
+            // a cluster that another alias still points to is kept
+            let still_used = aliases.values().any(|value| value == previous);
+            if drop_previous && !still_used {
+                if let Some(cluster_store) = store.remove(previous) {
+                    self.remove_cluster_settings(previous);
+                    self.unindex_cluster_tags(previous, &cluster_store);
+                    let total_size: usize = cluster_store
+                        .values()
+                        .map(|(v, _, _, _, _, _)| std::mem::size_of_val(v))
+                        .sum();
+                    self.memory_handler
+                        .lock()
+                        .unwrap()
+                        .delete_memory(total_size);
+                    dropped = true;
+                }
+            }
+        }
+
+        Logger::log_info_data(&format!("alias: {} points to cluster: {}", alias, target))
+            .write_log_to_file();
+        self.persist(
+            PersistentRecord::new("ALIAS")
+                .arg(alias)
+                .arg(&target)
+                .arg(drop_previous.to_string()),
+            ignore_persistent,
+        );
+        Ok(AliasResult {
+            alias: alias.to_string(),
+            target,
+            previous_target,
+            dropped,
+        })
+    }
+
+    fn remove_alias(&mut self, alias: &str, ignore_persistent: bool) -> bool {
+        let removed = self.aliases.lock().unwrap().remove(alias).is_some();
+        if removed {
+            Logger::log_info_data(&format!("alias: {} removed", alias)).write_log_to_file();
+            self.persist(
+                PersistentRecord::new("UNALIAS").arg(alias),
+                ignore_persistent,
+            );
+        }
+        removed
+    }
+
+    fn get_aliases(&self) -> BTreeMap<String, String> {
+        self.aliases.lock().unwrap().clone()
+    }
+}
+
+impl Cache {
+    // maps an alias to the cluster it currently points to, any other name is returned as is
+    pub fn resolve_cluster(&self, cluster: &str) -> String {
+        self.aliases
+            .lock()
+            .unwrap()
+            .get(cluster)
+            .cloned()
+            .unwrap_or_else(|| cluster.to_string())
+    }
+}
@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{logger::logger_manager::Logger, persistent::record::PersistentRecord};
+
+use super::{
+    cache::{CacheError, EvictionStrategy, Store},
+    Cache,
+};
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct ClusterConfig {
+    pub default_ttl: Option<u64>, // Duration in milliseconds, used when a write has no ttl
+    pub max_keys: Option<usize>,
+    pub eviction_strategy: Option<i32>, // same values as eviction_strategy in config.json
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+pub trait ClusterSettings {
+    fn set_cluster_config(
+        &mut self,
+        cluster: &str,
+        config: ClusterConfig,
+        ignore_persistent: bool,
+    ) -> Result<ClusterConfig, CacheError>;
+    fn get_cluster_config(&self, cluster: &str) -> Option<ClusterConfig>;
+}
+
+impl ClusterSettings for Cache {
+    fn set_cluster_config(
+        &mut self,
+        cluster: &str,
+        config: ClusterConfig,
+        ignore_persistent: bool,
+    ) -> Result<ClusterConfig, CacheError> {
+        let cluster = &self.resolve_cluster(cluster);
+        let valid_strategy = config.eviction_strategy.map_or(true, |strategy| {
+            EvictionStrategy::from_i32(strategy).is_some()
+        });
+        if !valid_strategy || config.max_keys == Some(0) || config.default_ttl == Some(0) {
+            return Err(CacheError::InvalidClusterConfig);
+        }
+
+        self.cluster_configs
+            .lock()
+            .unwrap()
+            .insert(cluster.to_string(), config.clone());
+
+        Logger::log_info_data(&format!("cluster: {} configured", cluster)).write_log_to_file();
+        if let Ok(json) = serde_json::to_string(&config) {
+            self.persist(
+                PersistentRecord::new("CLUSTER_CONFIG")
+                    .arg(cluster)
+                    .arg(json),
+                ignore_persistent,
+            );
+        }
+        Ok(config)
+    }
+
+    fn get_cluster_config(&self, cluster: &str) -> Option<ClusterConfig> {
+        let cluster = &self.resolve_cluster(cluster);
+        self.cluster_configs.lock().unwrap().get(cluster).cloned()
+    }
+}
+
+impl Cache {
+    pub fn cluster_default_ttl(&self, cluster: &str) -> Option<Duration> {
+        self.cluster_configs
+            .lock()
+            .unwrap()
+            .get(cluster)
+            .and_then(|config| config.default_ttl)
+            .map(Duration::from_millis)
+    }
+
+    pub fn is_cluster_read_only(&self, cluster: &str) -> bool {
+        self.cluster_configs
+            .lock()
+            .unwrap()
+            .get(cluster)
+            .map_or(false, |config| config.read_only)
+    }
+
+    // checks a write against the cluster settings; when `key` would be a new key of a full
+    // cluster, an entry of that cluster is evicted with its own strategy to make room
+    pub fn check_cluster_write(
+        &self,
+        store: &mut Store,
+        cluster: &str,
+        key: Option<&str>,
+        ignore_persistent: bool,
+    ) -> Result<(), CacheError> {
+        let config = match self.cluster_configs.lock().unwrap().get(cluster) {
+            Some(config) => config.clone(),
+            None => return Ok(()),
+        };
+        if config.read_only {
+            return Err(CacheError::ReadOnlyCluster);
+        }
+        let (max_keys, key) = match (config.max_keys, key) {
+            (Some(max_keys), Some(key)) => (max_keys, key),
+            _ => return Ok(()),
+        };
+
+        let cluster_store = match store.get_mut(cluster) {
+            Some(cluster_store) => cluster_store,
+            None => return Ok(()),
+        };
+        // the cluster keeps its own key count, expired entries not swept yet are included,
+        // so a cluster below its limit or an overwrite is accepted without looking at other entries
+        if cluster_store.len() < max_keys || cluster_store.contains_key(key) {
+            return Ok(());
+        }
+        // only a cluster at its limit has its expired entries swept, they do not count against it
+        self.remove_expired(cluster, cluster_store);
+        if cluster_store.len() < max_keys {
+            return Ok(());
+        }
+
+        let evict_type = config.eviction_strategy.unwrap_or(self.evict_type);
+        let mut memory_handler = self.memory_handler.lock().unwrap();
+        if self.evict_with_strategy(
+            store,
+            &mut memory_handler,
+            evict_type,
+            Some(cluster),
+            ignore_persistent,
+        ) {
+            Ok(())
+        } else {
+            Err(CacheError::ClusterFull)
+        }
+    }
+}
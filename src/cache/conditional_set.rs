@@ -0,0 +1,119 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use super::{
+    cache::{is_live, Store},
+    Cache,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SetCondition {
+    Always,
+    // NX: only write when the key does not exist
+    IfAbsent,
+    // XX: only write when the key already exists
+    IfExists,
+    // compare-and-swap: only write when the key is still at this version (0 = key must not exist)
+    IfVersion(u64),
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct SetOutcome {
+    pub written: bool,
+    pub version: Option<u64>,
+}
+
+pub trait ConditionalSet {
+    fn key_version(&self, cluster: &str, key: &str) -> Option<u64>;
+    fn set_if(
+        &mut self,
+        cluster: String,
+        key: String,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+        condition: SetCondition,
+        ignore_persistent: bool,
+    ) -> SetOutcome;
+}
+
+impl ConditionalSet for Cache {
+    fn key_version(&self, cluster: &str, key: &str) -> Option<u64> {
+        let cluster = &self.resolve_cluster(cluster);
+        let store = self.store.lock().unwrap();
+        live_version(&store, cluster, key)
+    }
+
+    fn set_if(
+        &mut self,
+        cluster: String,
+        key: String,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+        condition: SetCondition,
+        ignore_persistent: bool,
+    ) -> SetOutcome {
+        let cluster = self.resolve_cluster(&cluster);
+        let ttl = ttl.or_else(|| self.cluster_default_ttl(&cluster));
+        let has_memory = self.has_memory_for_write(&cluster, ignore_persistent);
+        let mut store = self.store.lock().unwrap();
+        if !has_memory {
+            return SetOutcome {
+                written: false,
+                version: live_version(&store, &cluster, &key),
+            };
+        }
+        self.set_if_in_store(
+            &mut store,
+            &cluster,
+            &key,
+            value,
+            ttl,
+            condition,
+            ignore_persistent,
+        )
+    }
+}
+
+impl Cache {
+    // checks the condition and writes under the same lock of the store, so no write can slip in between
+    pub fn set_if_in_store(
+        &self,
+        store: &mut Store,
+        cluster: &str,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+        condition: SetCondition,
+        ignore_persistent: bool,
+    ) -> SetOutcome {
+        let current_version = live_version(store, cluster, key);
+        let can_write = match condition {
+            SetCondition::Always => true,
+            SetCondition::IfAbsent => current_version.is_none(),
+            SetCondition::IfExists => current_version.is_some(),
+            SetCondition::IfVersion(expected) => current_version.unwrap_or(0) == expected,
+        };
+        if !can_write {
+            return SetOutcome {
+                written: false,
+                version: current_version,
+            };
+        }
+
+        let written = self.set_in_store(store, cluster, key, value, ttl, ignore_persistent);
+        SetOutcome {
+            written,
+            version: live_version(store, cluster, key),
+        }
+    }
+}
+
+// the version of a key that has not expired yet
+fn live_version(store: &Store, cluster: &str, key: &str) -> Option<u64> {
+    store
+        .get(cluster)
+        .and_then(|cluster_store| cluster_store.get(key))
+        .filter(|entry| is_live(entry, Instant::now()))
+        .map(|(_, _, _, _, version, _)| *version)
+}
@@ -0,0 +1,87 @@
+use std::time::Instant;
+
+use crate::{logger::logger_manager::Logger, persistent::record::PersistentRecord};
+
+use super::{
+    cache::{is_live, CacheError},
+    Cache,
+};
+
+pub trait CopyKey {
+    // Ok(false) when the destination key exists and `replace` is not set
+    fn copy_key(
+        &mut self,
+        src_cluster: &str,
+        key: &str,
+        desc_cluster: &str,
+        desc_key: &str,
+        replace: bool,
+        ignore_persistent: bool,
+    ) -> Result<bool, CacheError>;
+}
+
+impl CopyKey for Cache {
+    fn copy_key(
+        &mut self,
+        src_cluster: &str,
+        key: &str,
+        desc_cluster: &str,
+        desc_key: &str,
+        replace: bool,
+        ignore_persistent: bool,
+    ) -> Result<bool, CacheError> {
+        let src_cluster = &self.resolve_cluster(src_cluster);
+        let desc_cluster = &self.resolve_cluster(desc_cluster);
+        if !self.has_memory_for_write(desc_cluster, ignore_persistent) {
+            return Err(CacheError::MemoryLimitExceeded);
+        }
+
+        let next_version = self.next_version();
+        let mut store = self.store.lock().unwrap();
+        let now = Instant::now();
+        let mut entry = store
+            .get(src_cluster)
+            .and_then(|cluster_store| cluster_store.get(key))
+            .filter(|entry| is_live(entry, now))
+            .cloned()
+            .ok_or(CacheError::KeyNotFound)?;
+        if src_cluster == desc_cluster && key == desc_key {
+            return Ok(true);
+        }
+        let target_exists = store
+            .get(desc_cluster)
+            .and_then(|cluster_store| cluster_store.get(desc_key))
+            .map_or(false, |entry| is_live(entry, now));
+        if target_exists && !replace {
+            return Ok(false);
+        }
+        self.check_cluster_write(&mut store, desc_cluster, Some(desc_key), ignore_persistent)?;
+
+        // the copy keeps the type and the remaining ttl of the source
+        entry.4 = next_version;
+        entry.5 = entry.5.copied();
+        let mut memory_handler = self.memory_handler.lock().unwrap();
+        memory_handler.add_memory(std::mem::size_of_val(&entry.0));
+        let desc_store = store.entry(desc_cluster.to_string()).or_default();
+        // the replaced entry leaves the tag index before the new one joins it, they may share tags
+        if let Some(replaced) = desc_store.remove(desc_key) {
+            self.unindex_entry_tags(desc_cluster, desc_key, &replaced);
+            memory_handler.delete_memory(std::mem::size_of_val(&replaced.0));
+        }
+        self.index_entry_tags(desc_cluster, desc_key, &entry);
+        desc_store.insert(desc_key.to_string(), entry);
+
+        if self.enable_log {
+            Logger::log_info("key copied").write_log_to_file();
+        }
+        self.persist(
+            PersistentRecord::new("COPY")
+                .arg(src_cluster)
+                .arg(key)
+                .arg(desc_cluster)
+                .arg(desc_key),
+            ignore_persistent,
+        );
+        Ok(true)
+    }
+}
@@ -1,12 +1,16 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::{
     convert::{i32_to_vec, vec_to_i32},
     logger::logger_manager::Logger,
-    persistent::persistent_Manager,
 };
 
-use super::{cache::CacheType, get::Get, Cache};
+use super::{
+    cache::{CacheType, EntryMeta},
+    get::Get,
+    incr::set_int_record,
+    Cache,
+};
 
 pub trait Decr {
     fn decr(
@@ -26,6 +30,7 @@ impl Decr for Cache {
         value: Option<i32>,
         ignore_persistent: bool,
     ) -> bool {
+        let cluster = self.resolve_cluster(&cluster);
         let mut deccrement_value: Option<Vec<u8>> = None;
         if value.is_some() {
             let main_value = Option::Some(value.as_ref().unwrap().to_le_bytes().to_vec());
@@ -37,26 +42,36 @@ impl Decr for Cache {
         let get_cache = self.get(&cluster, &key);
         if get_cache.value.is_some() {
             // decrement logic
+            let next_version = self.next_version();
             let mut store = self.store.lock().unwrap();
-            let cluster_store = store.entry(cluster.clone()).or_insert_with(HashMap::new);
-            let current_value = cluster_store
+            if self
+                .check_cluster_write(&mut store, &cluster, None, ignore_persistent)
+                .is_err()
+            {
+                return false;
+            }
+            let cluster_store = store.entry(cluster.clone()).or_insert_with(BTreeMap::new);
+            let entry = cluster_store
                 .entry(key.clone())
-                .and_modify(|(existing_value, _, _, cache_type)| {
+                .and_modify(|(existing_value, _, _, cache_type, version, meta)| {
                     // Convert Vec<u8> to [u8; 4] and then to i32
                     let mut current_i32 =
                         i32::from_le_bytes(existing_value[..4].try_into().unwrap());
                     current_i32 -= vec_to_i32(deccrement_value.clone().unwrap()).unwrap();
                     // Update the value as Vec<u8>
                     *existing_value = current_i32.to_le_bytes().to_vec();
+                    *version = next_version;
+                    meta.touch_write();
                 })
                 .or_insert((
                     deccrement_value.clone().unwrap(),
                     None,
                     None,
                     CacheType::Int,
-                ))
-                .0
-                .clone();
+                    next_version,
+                    EntryMeta::new(),
+                ));
+            let (current_value, expiration) = (entry.0.clone(), entry.1);
 
             // Memory management
             let mut memory_handler = self.memory_handler.lock().unwrap();
@@ -70,10 +85,10 @@ impl Decr for Cache {
                 ))
                 .write_log_to_file();
             }
-            if self.persistent && !ignore_persistent {
-                let command = format!("DECR {} {} {:?}", cluster, key, current_value);
-                persistent_Manager::write_to_persistent_file(&command);
-            }
+            self.persist(
+                set_int_record(&cluster, &key, &current_value, expiration),
+                ignore_persistent,
+            );
             true
         } else {
             Logger::log_error(&format!(
@@ -1,28 +1,53 @@
-use crate::{logger::logger_manager::Logger, persistent::persistent_Manager};
+use crate::{logger::logger_manager::Logger, persistent::record::PersistentRecord};
 
-use super::Cache;
+use super::{cache::Store, Cache};
 
 pub trait Delete {
-    fn delete(&self, cluster: &str, key: &str, ignore_persistent: bool);
+    // tells whether an entry was removed
+    fn delete(&self, cluster: &str, key: &str, ignore_persistent: bool) -> bool;
 }
 
 impl Delete for Cache {
-    fn delete(&self, cluster: &str, key: &str, ignore_persistent: bool) {
+    fn delete(&self, cluster: &str, key: &str, ignore_persistent: bool) -> bool {
+        let cluster = &self.resolve_cluster(cluster);
         let mut store = self.store.lock().unwrap();
-        if let Some(cluster_store) = store.get_mut(cluster) {
-            if let Some((value, _, _, _)) = cluster_store.remove(key) {
-                let mut memory_handler = self.memory_handler.lock().unwrap();
-                let memory_usage = std::mem::size_of_val(&value);
-                memory_handler.delete_memory(memory_usage);
-                if self.enable_log == true {
-                    let delete_log = Logger::log_info("value deleted ");
-                    delete_log.write_log_to_file();
-                    if self.persistent && !ignore_persistent {
-                        let command = format!("DEL {} {}", cluster.clone(), key.clone());
-                        persistent_Manager::write_to_persistent_file(&command);
-                    }
-                }
-            }
+        self.delete_in_store(&mut store, cluster, key, ignore_persistent)
+    }
+}
+
+impl Cache {
+    // removes a key from an already locked store, tells whether an entry was removed
+    pub fn delete_in_store(
+        &self,
+        store: &mut Store,
+        cluster: &str,
+        key: &str,
+        ignore_persistent: bool,
+    ) -> bool {
+        if self
+            .check_cluster_write(store, cluster, None, ignore_persistent)
+            .is_err()
+        {
+            return false;
+        }
+        let Some(entry) = store
+            .get_mut(cluster)
+            .and_then(|cluster_store| cluster_store.remove(key))
+        else {
+            return false;
+        };
+        self.unindex_entry_tags(cluster, key, &entry);
+        let mut memory_handler = self.memory_handler.lock().unwrap();
+        let memory_usage = std::mem::size_of_val(&entry.0);
+        memory_handler.delete_memory(memory_usage);
+        if self.enable_log == true {
+            let delete_log = Logger::log_info("value deleted ");
+            delete_log.write_log_to_file();
         }
+        self.persist(
+            PersistentRecord::new("DEL").arg(cluster).arg(key),
+            ignore_persistent,
+        );
+        true
     }
 }
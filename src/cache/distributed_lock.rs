@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use serde::Serialize;
+
+use crate::logger::logger_manager::Logger;
+
+use super::{cache::CacheError, Cache};
+
+#[derive(Clone, Debug)]
+pub struct LockEntry {
+    pub owner: String,
+    pub expires_at: Instant,
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct LockInfo {
+    pub name: String,
+    pub owner: String,
+    pub ttl: u64, // remaining time in milliseconds
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct LockResult {
+    pub acquired: bool,
+    pub lock: Option<LockInfo>,
+}
+
+pub trait DistributedLock {
+    fn acquire_lock(
+        &self,
+        name: &str,
+        ttl: Duration,
+        token: Option<String>,
+    ) -> Result<LockResult, CacheError>;
+    fn release_lock(&self, name: &str, token: &str) -> bool;
+    fn extend_lock(
+        &self,
+        name: &str,
+        token: &str,
+        ttl: Duration,
+    ) -> Result<Option<LockInfo>, CacheError>;
+    fn lock_holder(&self, name: &str) -> Option<LockInfo>;
+}
+
+impl DistributedLock for Cache {
+    fn acquire_lock(
+        &self,
+        name: &str,
+        ttl: Duration,
+        token: Option<String>,
+    ) -> Result<LockResult, CacheError> {
+        let now = Instant::now();
+        let expires_at = now.checked_add(ttl).ok_or(CacheError::TtlOutOfRange)?;
+        let mut locks = self.locks.lock().unwrap();
+        remove_if_expired(&mut locks, name, now);
+
+        let owner = token.unwrap_or_else(generate_lock_token);
+        if let Some(current) = locks.get(name) {
+            // a held lock can only be taken again by its owner, which renews it
+            if current.owner != owner {
+                return Ok(LockResult {
+                    acquired: false,
+                    lock: Some(lock_info(name, current, now)),
+                });
+            }
+        }
+
+        let entry = LockEntry { owner, expires_at };
+        let info = lock_info(name, &entry, now);
+        locks.insert(name.to_string(), entry);
+        if self.enable_log {
+            Logger::log_info_data(&format!("lock {} acquired", name)).write_log_to_file();
+        }
+        Ok(LockResult {
+            acquired: true,
+            lock: Some(info),
+        })
+    }
+
+    fn release_lock(&self, name: &str, token: &str) -> bool {
+        let mut locks = self.locks.lock().unwrap();
+        remove_if_expired(&mut locks, name, Instant::now());
+
+        match locks.get(name) {
+            Some(current) if current.owner == token => {
+                locks.remove(name);
+                if self.enable_log {
+                    Logger::log_info_data(&format!("lock {} released", name)).write_log_to_file();
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn extend_lock(
+        &self,
+        name: &str,
+        token: &str,
+        ttl: Duration,
+    ) -> Result<Option<LockInfo>, CacheError> {
+        let now = Instant::now();
+        let expires_at = now.checked_add(ttl).ok_or(CacheError::TtlOutOfRange)?;
+        let mut locks = self.locks.lock().unwrap();
+        remove_if_expired(&mut locks, name, now);
+
+        match locks.get_mut(name) {
+            Some(current) if current.owner == token => {
+                current.expires_at = expires_at;
+                Ok(Some(lock_info(name, current, now)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn lock_holder(&self, name: &str) -> Option<LockInfo> {
+        let locks = self.locks.lock().unwrap();
+        let now = Instant::now();
+        locks
+            .get(name)
+            .filter(|lock| lock.expires_at > now)
+            .map(|lock| lock_info(name, lock, now))
+    }
+}
+
+impl Cache {
+    // locks nobody asks for again are dropped here instead of on every lock call
+    pub fn clear_expired_locks(&self) {
+        let now = Instant::now();
+        self.locks
+            .lock()
+            .unwrap()
+            .retain(|_, lock| lock.expires_at > now);
+    }
+}
+
+// a lock is only looked at by its name, so only that one entry has to be checked for expiry
+fn remove_if_expired(locks: &mut HashMap<String, LockEntry>, name: &str, now: Instant) {
+    if locks.get(name).map_or(false, |lock| lock.expires_at <= now) {
+        locks.remove(name);
+    }
+}
+
+fn lock_info(name: &str, lock: &LockEntry, now: Instant) -> LockInfo {
+    LockInfo {
+        name: name.to_string(),
+        owner: lock.owner.clone(),
+        ttl: lock.expires_at.saturating_duration_since(now).as_millis() as u64,
+    }
+}
+
+fn generate_lock_token() -> String {
+    let bytes: [u8; 16] = rand::thread_rng().gen();
+    hex::encode(bytes)
+}
@@ -0,0 +1,71 @@
+use crate::{logger::logger_manager::Logger, persistent::record::PersistentRecord};
+
+use super::Cache;
+
+pub trait DropCluster {
+    fn drop_cluster(&mut self, cluster: &str, ignore_persistent: bool) -> bool;
+}
+
+impl DropCluster for Cache {
+    // removes the cluster itself along with its keys, settings, expiry and the aliases pointing to it,
+    // a write through such an alias would otherwise create a new cluster under the dropped name
+    fn drop_cluster(&mut self, cluster: &str, ignore_persistent: bool) -> bool {
+        let cluster = &self.resolve_cluster(cluster);
+        let (removed, removed_aliases) = {
+            // store and aliases stay locked together so no write slips through an alias in between
+            let mut store = self.store.lock().unwrap();
+            let mut aliases = self.aliases.lock().unwrap();
+            let removed = store.remove(cluster);
+            let mut removed_aliases = Vec::new();
+            if removed.is_some() {
+                aliases.retain(|alias, target| {
+                    if target == cluster {
+                        removed_aliases.push(alias.clone());
+                        false
+                    } else {
+                        true
+                    }
+                });
+            }
+            (removed, removed_aliases)
+        };
+        self.remove_cluster_settings(cluster);
+        let cluster_store = match removed {
+            Some(cluster_store) => cluster_store,
+            None => return false,
+        };
+        for alias in removed_aliases.iter() {
+            Logger::log_info_data(&format!("alias: {} removed with its cluster", alias))
+                .write_log_to_file();
+            self.persist(
+                PersistentRecord::new("UNALIAS").arg(alias),
+                ignore_persistent,
+            );
+        }
+        self.unindex_cluster_tags(cluster, &cluster_store);
+
+        let total_size: usize = cluster_store
+            .values()
+            .map(|(v, _, _, _, _, _)| std::mem::size_of_val(v))
+            .sum();
+        self.memory_handler
+            .lock()
+            .unwrap()
+            .delete_memory(total_size);
+
+        Logger::log_info_data(&format!("cluster: {} dropped", cluster)).write_log_to_file();
+        self.persist(
+            PersistentRecord::new("DROP_CLUSTER").arg(cluster),
+            ignore_persistent,
+        );
+        true
+    }
+}
+
+impl Cache {
+    // a cluster created later under the same name must not inherit the limits or expiry of a dropped one
+    pub fn remove_cluster_settings(&self, cluster: &str) {
+        self.cluster_configs.lock().unwrap().remove(cluster);
+        self.cluster_expirations.lock().unwrap().remove(cluster);
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::{HashSet, VecDeque};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::convert::vec_to_i32;
+
+use super::{
+    cache::{CacheError, CacheType},
+    clear_cluster::ClearCluster,
+    get_all_clusters::GetAllClusters,
+    scan::Scan,
+    tag_index::{valid_tag, Tagging},
+    Cache,
+};
+
+// how many entries an export reads per lock of the cache
+pub const EXPORT_PAGE_SIZE: usize = 500;
+// an import summary keeps only the first errors so a broken dump does not blow up the response
+const MAX_REPORTED_ERRORS: usize = 20;
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DumpValueType {
+    String,
+    Integer,
+}
+
+// one key of a dump, written as a single json line
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct DumpEntry {
+    pub cluster: String,
+    pub key: String,
+    #[serde(rename = "type")]
+    pub value_type: DumpValueType,
+    // a number for integers, text for strings unless `encoding` says it is base64
+    pub value: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    // milliseconds left at the time of the export
+    #[serde(default)]
+    pub ttl: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+#[derive(Clone, Copy, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    // keys of the dump overwrite existing ones, everything else is kept
+    #[default]
+    Merge,
+    // every cluster in the dump is cleared before its first key is imported
+    Replace,
+}
+
+impl FromStr for ImportMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "merge" => Ok(ImportMode::Merge),
+            "replace" => Ok(ImportMode::Replace),
+            _ => Err(format!(
+                "unknown import mode {}, use merge or replace",
+                value
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Debug, Default)]
+pub struct ImportSummary {
+    pub imported: u64,
+    pub skipped: u64,
+    pub replaced_clusters: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+pub trait Dump {
+    fn export_page(
+        &self,
+        cluster: &str,
+        cursor: Option<&str>,
+        count: usize,
+    ) -> Result<(Vec<DumpEntry>, Option<String>), String>;
+    fn import_entry(&mut self, entry: &DumpEntry, ignore_persistent: bool) -> Result<(), String>;
+}
+
+impl Dump for Cache {
+    fn export_page(
+        &self,
+        cluster: &str,
+        cursor: Option<&str>,
+        count: usize,
+    ) -> Result<(Vec<DumpEntry>, Option<String>), String> {
+        let cluster = &self.resolve_cluster(cluster);
+        let page = self.scan_keys(cluster, cursor, count, None)?;
+        let now = Instant::now();
+        let store = self.store.lock().unwrap();
+        let entries = page
+            .items
+            .iter()
+            .filter_map(|key| {
+                let (value, expiration_time, _, cache_type, _, meta) =
+                    store.get(cluster)?.get(key)?;
+                let (value_type, value, encoding) = match cache_type {
+                    CacheType::Int => (
+                        DumpValueType::Integer,
+                        serde_json::Value::from(vec_to_i32(value.clone())?),
+                        None,
+                    ),
+                    CacheType::Str => match String::from_utf8(value.clone()) {
+                        Ok(text) => (DumpValueType::String, serde_json::Value::from(text), None),
+                        Err(_) => (
+                            DumpValueType::String,
+                            serde_json::Value::from(base64::encode(value)),
+                            Some("base64".to_string()),
+                        ),
+                    },
+                };
+                Some(DumpEntry {
+                    cluster: cluster.clone(),
+                    key: key.clone(),
+                    value_type,
+                    value,
+                    encoding,
+                    ttl: expiration_time
+                        .map(|exp| exp.saturating_duration_since(now).as_millis() as u64),
+                    tags: meta.tags.clone(),
+                })
+            })
+            .collect();
+        Ok((entries, page.cursor))
+    }
+
+    // goes through the regular write operations so limits, tags and persistence apply as usual.
+    // an entry exported without a ttl is imported without one, the cluster default is not applied
+    fn import_entry(&mut self, entry: &DumpEntry, ignore_persistent: bool) -> Result<(), String> {
+        if !entry.tags.iter().all(|tag| valid_tag(tag)) {
+            return Err(CacheError::InvalidTag.as_str().to_string());
+        }
+        let ttl = entry.ttl.map(Duration::from_millis);
+        let written = match entry.value_type {
+            DumpValueType::Integer => {
+                let value = entry
+                    .value
+                    .as_i64()
+                    .and_then(|value| i32::try_from(value).ok())
+                    .ok_or_else(|| "integer value does not fit in 32 bits".to_string())?;
+                self.set_int(&entry.cluster, &entry.key, value, ttl, ignore_persistent)
+            }
+            DumpValueType::String => {
+                let text = entry
+                    .value
+                    .as_str()
+                    .ok_or_else(|| "string value expected".to_string())?;
+                let value = match entry.encoding.as_deref() {
+                    None | Some("utf8") => text.as_bytes().to_vec(),
+                    Some("base64") => {
+                        base64::decode(text).map_err(|_| "invalid base64 value".to_string())?
+                    }
+                    Some(encoding) => return Err(format!("unknown encoding {}", encoding)),
+                };
+                self.set_exact(&entry.cluster, &entry.key, value, ttl, ignore_persistent)
+            }
+        };
+        if !written {
+            return Err("write rejected by the cache".to_string());
+        }
+        if !entry.tags.is_empty() {
+            self.tag_key(&entry.cluster, &entry.key, &entry.tags, ignore_persistent)
+                .map_err(|error| error.as_str().to_string())?;
+        }
+        Ok(())
+    }
+}
+
+// walks the given clusters, or all of them when none are given, page by page.
+// the cache only has to be held while a page is read
+pub struct DumpExport {
+    clusters: VecDeque<String>,
+    cursor: Option<String>,
+}
+
+impl DumpExport {
+    pub fn new(cache: &Cache, clusters: &[String]) -> Result<Self, String> {
+        let mut clusters: Vec<String> = if clusters.is_empty() {
+            cache.get_all_clusters()
+        } else {
+            let store = cache.store.lock().unwrap();
+            clusters
+                .iter()
+                .map(|cluster| {
+                    let cluster = cache.resolve_cluster(cluster);
+                    if store.contains_key(&cluster) {
+                        Ok(cluster)
+                    } else {
+                        Err(format!("cluster {} not found", cluster))
+                    }
+                })
+                .collect::<Result<_, _>>()?
+        };
+        clusters.sort();
+        clusters.dedup();
+        Ok(DumpExport {
+            clusters: clusters.into(),
+            cursor: None,
+        })
+    }
+
+    pub fn clusters(&self) -> impl Iterator<Item = &String> {
+        self.clusters.iter()
+    }
+
+    // the next non empty page, None once every cluster is exported
+    pub fn next_page(&mut self, cache: &Cache) -> Option<Vec<DumpEntry>> {
+        loop {
+            let cluster = self.clusters.front()?.clone();
+            let page = cache.export_page(&cluster, self.cursor.as_deref(), EXPORT_PAGE_SIZE);
+            // a cluster dropped while the export runs is simply left out
+            let (entries, cursor) = page.unwrap_or((Vec::new(), None));
+            self.cursor = cursor;
+            if self.cursor.is_none() {
+                self.clusters.pop_front();
+            }
+            if !entries.is_empty() {
+                return Some(entries);
+            }
+        }
+    }
+
+    // the next page as json lines
+    pub fn next_chunk(&mut self, cache: &Cache) -> Option<Vec<u8>> {
+        let mut chunk = Vec::new();
+        for entry in self.next_page(cache)?.iter() {
+            serde_json::to_writer(&mut chunk, entry).ok()?;
+            chunk.push(b'\n');
+        }
+        Some(chunk)
+    }
+}
+
+// feeds a dump into the cache line by line, so it never has to be held in memory as a whole
+pub struct DumpImporter {
+    mode: ImportMode,
+    seen_clusters: HashSet<String>,
+    pub summary: ImportSummary,
+}
+
+impl DumpImporter {
+    pub fn new(mode: ImportMode) -> Self {
+        DumpImporter {
+            mode,
+            seen_clusters: HashSet::new(),
+            summary: ImportSummary::default(),
+        }
+    }
+
+    pub fn import_line(&mut self, cache: &mut Cache, line: &str) {
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        let line_number = self.summary.imported + self.summary.skipped + 1;
+        match serde_json::from_str::<DumpEntry>(line) {
+            Ok(entry) => {
+                if let Err(error) = self.import(cache, entry) {
+                    self.report(format!("line {}: {}", line_number, error));
+                }
+            }
+            Err(error) => self.report(format!("line {}: {}", line_number, error)),
+        }
+    }
+
+    pub fn import(&mut self, cache: &mut Cache, mut entry: DumpEntry) -> Result<(), String> {
+        entry.cluster = cache.resolve_cluster(&entry.cluster);
+        if self.seen_clusters.insert(entry.cluster.clone()) && self.mode == ImportMode::Replace {
+            cache.clear_cluster(&entry.cluster, false);
+            self.summary.replaced_clusters.push(entry.cluster.clone());
+        }
+        cache.import_entry(&entry, false)?;
+        self.summary.imported += 1;
+        Ok(())
+    }
+
+    pub fn report(&mut self, error: String) {
+        self.summary.skipped += 1;
+        if self.summary.errors.len() < MAX_REPORTED_ERRORS {
+            self.summary.errors.push(error);
+        }
+    }
+}
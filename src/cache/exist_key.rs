@@ -6,12 +6,13 @@ pub trait KeyExists {
 
 impl KeyExists for Cache {
     fn exists(&self, cluster: &String, key: &String) -> bool {
+        let cluster = &self.resolve_cluster(cluster);
         let store = self.store.lock().unwrap();
         let value = store.get(cluster).and_then(|cluster_store| {
             cluster_store
                 .get(key)
                 .cloned()
-                .map(|(value, _, _, _)| value)
+                .map(|(value, _, _, _, _, _)| value)
         });
         if value.is_some() {
             true
@@ -0,0 +1,76 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    logger::logger_manager::Logger,
+    persistent::record::{self, PersistentRecord},
+};
+
+use super::{drop_cluster::DropCluster, Cache};
+
+pub trait ExpireCluster {
+    fn expire_cluster(
+        &mut self,
+        cluster: &str,
+        ttl: Option<Duration>,
+        ignore_persistent: bool,
+    ) -> bool;
+    fn cluster_ttl(&self, cluster: &str) -> Option<Duration>;
+    fn drop_expired_clusters(&mut self) -> Vec<String>;
+}
+
+impl ExpireCluster for Cache {
+    // a ttl of None removes the expiry of the cluster
+    fn expire_cluster(
+        &mut self,
+        cluster: &str,
+        ttl: Option<Duration>,
+        ignore_persistent: bool,
+    ) -> bool {
+        let cluster = &self.resolve_cluster(cluster);
+        if !self.store.lock().unwrap().contains_key(cluster) {
+            return false;
+        }
+        let mut expirations = self.cluster_expirations.lock().unwrap();
+        match ttl {
+            Some(ttl) => expirations.insert(cluster.to_string(), Instant::now() + ttl),
+            None => expirations.remove(cluster),
+        };
+
+        if self.enable_log {
+            Logger::log_info("cluster expiry set").write_log_to_file();
+        }
+        let record = match ttl {
+            Some(ttl) => PersistentRecord::new("EXPIRE_CLUSTER")
+                .arg(cluster)
+                .arg(record::deadline_millis(ttl).to_string()),
+            None => PersistentRecord::new("PERSIST_CLUSTER").arg(cluster),
+        };
+        self.persist(record, ignore_persistent);
+        true
+    }
+
+    fn cluster_ttl(&self, cluster: &str) -> Option<Duration> {
+        let cluster = &self.resolve_cluster(cluster);
+        self.cluster_expirations
+            .lock()
+            .unwrap()
+            .get(cluster)
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    fn drop_expired_clusters(&mut self) -> Vec<String> {
+        let now = Instant::now();
+        let expired: Vec<String> = self
+            .cluster_expirations
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(cluster, _)| cluster.clone())
+            .collect();
+        for cluster in expired.iter() {
+            self.drop_cluster(cluster, false);
+        }
+        expired
+    }
+}
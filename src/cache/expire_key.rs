@@ -1,23 +1,57 @@
 use super::Cache;
-use std::collections::HashMap;
-use std::time::Instant;
+use crate::persistent::record::{self, PersistentRecord};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
 
 pub trait ExpireKey {
-    fn expire_key(&mut self, cluster: &String, key: &String, new_ttl: &u64) -> bool;
+    fn expire_key(
+        &mut self,
+        cluster: &String,
+        key: &String,
+        new_ttl: &u64,
+        ignore_persistent: bool,
+    ) -> bool;
 }
 
 impl ExpireKey for Cache {
-    fn expire_key(&mut self, cluster: &String, key: &String, new_ttl: &u64) -> bool {
+    fn expire_key(
+        &mut self,
+        cluster: &String,
+        key: &String,
+        new_ttl: &u64,
+        ignore_persistent: bool,
+    ) -> bool {
+        let cluster = &self.resolve_cluster(cluster);
         let mut store = self.store.lock().unwrap();
-        let cluster_store = store.entry(cluster.clone()).or_insert_with(HashMap::new);
+        if self
+            .check_cluster_write(&mut store, cluster, None, ignore_persistent)
+            .is_err()
+        {
+            return false;
+        }
+        let cluster_store = store.entry(cluster.clone()).or_insert_with(BTreeMap::new);
         let duration = Option::Some(std::time::Duration::from_millis(*new_ttl));
         let expiration_time = duration.map(|dr| Instant::now() + dr);
-        if let Some((value, expite_time, ttl, _)) = cluster_store.get_mut(key) {
+        let next_version = self.next_version();
+        if let Some((value, expite_time, ttl, _, version, meta)) = cluster_store.get_mut(key) {
             *expite_time = expiration_time;
             *ttl = Option::Some(std::time::Duration::from_millis(*new_ttl));
+            *version = next_version;
+            meta.touch_write();
+            self.persist(
+                expire_record(cluster, key, Duration::from_millis(*new_ttl)),
+                ignore_persistent,
+            );
             true
         } else {
             false
         }
     }
 }
+
+pub fn expire_record(cluster: &str, key: &str, ttl: Duration) -> PersistentRecord {
+    PersistentRecord::new("EXPIRE")
+        .arg(cluster)
+        .arg(key)
+        .arg(record::deadline_millis(ttl).to_string())
+}
@@ -1,9 +1,7 @@
-use std::time::Instant;
-
 use crate::logger::logger_manager::Logger;
 
 use super::{
-    cache::{CacheType, ResultValue},
+    cache::{CacheType, ResultValue, Store},
     Cache,
 };
 
@@ -13,28 +11,40 @@ pub trait Get {
 
 impl Get for Cache {
     fn get(&self, cluster: &str, key: &str) -> ResultValue {
+        let cluster = &self.resolve_cluster(cluster);
         let mut store = self.store.lock().unwrap();
+        self.get_in_store(&mut store, cluster, key)
+    }
+}
 
+impl Cache {
+    // reads a value from an already locked store, so callers can read and write under the same lock
+    pub fn get_in_store(&self, store: &mut Store, cluster: &str, key: &str) -> ResultValue {
         if let Some(cluster_store) = store.get_mut(cluster) {
-            cluster_store.retain(|k, (_, expiration_time, _, _)| {
-                if let Some(exp) = expiration_time {
-                    exp > &mut Instant::now()
-                } else {
-                    true
-                }
-            });
+            self.remove_expired(cluster, cluster_store);
+        }
+        if let Some(entry) = store
+            .get_mut(cluster)
+            .and_then(|cluster_store| cluster_store.get_mut(key))
+        {
+            entry.5.touch_read();
         }
         let value = store.get(cluster).and_then(|cluster_store| {
             cluster_store
                 .get(key)
                 .cloned()
-                .map(|(value, _, _, _)| value)
+                .map(|(value, _, _, _, _, _)| value)
         });
         let cahe_type = store.get(cluster).and_then(|cluster_store| {
             cluster_store
                 .get(key)
                 .cloned()
-                .map(|(_, _, _, cache_type)| cache_type)
+                .map(|(_, _, _, cache_type, _, _)| cache_type)
+        });
+        let version = store.get(cluster).and_then(|cluster_store| {
+            cluster_store
+                .get(key)
+                .map(|(_, _, _, _, version, _)| *version)
         });
         let mut value_type = None;
         if ((cahe_type.as_ref().is_some())
@@ -56,6 +66,7 @@ impl Get for Cache {
         return ResultValue {
             value: value,
             value_type: value_type,
+            version: version,
         };
     }
 }
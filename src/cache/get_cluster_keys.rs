@@ -1,14 +1,28 @@
-use super::Cache;
+use super::{key_pattern::KeyPattern, Cache};
 
 pub trait GetClusterKeys {
     fn get_keys_of_cluster(&self, cluster: &str) -> Option<Vec<String>>;
+    fn get_keys_matching(&self, cluster: &str, pattern: &KeyPattern) -> Option<Vec<String>>;
 }
 
 impl GetClusterKeys for Cache {
     fn get_keys_of_cluster(&self, cluster: &str) -> Option<Vec<String>> {
+        let cluster = &self.resolve_cluster(cluster);
         let store = self.store.lock().unwrap();
         store
             .get(cluster)
             .map(|cluster_store| cluster_store.keys().cloned().collect())
     }
+
+    fn get_keys_matching(&self, cluster: &str, pattern: &KeyPattern) -> Option<Vec<String>> {
+        let cluster = &self.resolve_cluster(cluster);
+        let store = self.store.lock().unwrap();
+        store.get(cluster).map(|cluster_store| {
+            cluster_store
+                .keys()
+                .filter(|key| pattern.is_match(key))
+                .cloned()
+                .collect()
+        })
+    }
 }
@@ -0,0 +1,18 @@
+use super::{cache::ResultValue, Cache};
+
+pub trait GetDel {
+    fn get_del(&mut self, cluster: &str, key: &str, ignore_persistent: bool) -> ResultValue;
+}
+
+impl GetDel for Cache {
+    fn get_del(&mut self, cluster: &str, key: &str, ignore_persistent: bool) -> ResultValue {
+        let cluster = &self.resolve_cluster(cluster);
+        // the value is read and removed under one lock so no write can slip in between
+        let mut store = self.store.lock().unwrap();
+        let result = self.get_in_store(&mut store, cluster, key);
+        if result.value.is_some() {
+            self.delete_in_store(&mut store, cluster, key, ignore_persistent);
+        }
+        result
+    }
+}
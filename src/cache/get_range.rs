@@ -0,0 +1,35 @@
+use super::{
+    cache::CacheError,
+    string_value::{live_string_entry, resolve_range},
+    Cache,
+};
+
+pub trait GetRange {
+    fn get_range(
+        &self,
+        cluster: &str,
+        key: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<u8>, CacheError>;
+}
+
+impl GetRange for Cache {
+    fn get_range(
+        &self,
+        cluster: &str,
+        key: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<u8>, CacheError> {
+        let cluster = &self.resolve_cluster(cluster);
+        let mut store = self.store.lock().unwrap();
+        let range = live_string_entry(store.get_mut(cluster), key)?.and_then(
+            |(value, _, _, _, _, meta)| {
+                meta.touch_read();
+                resolve_range(value.len(), start, end).map(|(from, to)| value[from..=to].to_vec())
+            },
+        );
+        Ok(range.unwrap_or_default())
+    }
+}
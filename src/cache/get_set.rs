@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+use super::{cache::ResultValue, conditional_set::SetCondition, Cache};
+
+#[derive(Clone, Serialize)]
+pub struct GetSetResult {
+    pub previous: ResultValue,
+    pub written: bool,
+    pub version: Option<u64>,
+}
+
+pub trait GetSet {
+    fn get_set(
+        &mut self,
+        cluster: String,
+        key: String,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+        ignore_persistent: bool,
+    ) -> GetSetResult;
+}
+
+impl GetSet for Cache {
+    fn get_set(
+        &mut self,
+        cluster: String,
+        key: String,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+        ignore_persistent: bool,
+    ) -> GetSetResult {
+        let cluster = self.resolve_cluster(&cluster);
+        let ttl = ttl.or_else(|| self.cluster_default_ttl(&cluster));
+        let has_memory = self.has_memory_for_write(&cluster, ignore_persistent);
+        // the previous value is read and the new one written under one lock so no write can slip in between
+        let mut store = self.store.lock().unwrap();
+        let previous = self.get_in_store(&mut store, &cluster, &key);
+        if !has_memory {
+            let version = previous.version;
+            return GetSetResult {
+                previous,
+                written: false,
+                version,
+            };
+        }
+        let outcome = self.set_if_in_store(
+            &mut store,
+            &cluster,
+            &key,
+            value,
+            ttl,
+            SetCondition::Always,
+            ignore_persistent,
+        );
+        GetSetResult {
+            previous,
+            written: outcome.written,
+            version: outcome.version,
+        }
+    }
+}
@@ -1,12 +1,16 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
 
 use crate::{
     convert::{i32_to_vec, vec_to_i32},
     logger::logger_manager::Logger,
-    persistent::persistent_Manager,
+    persistent::record::{self, PersistentRecord},
 };
 
-use super::{cache::CacheType, Cache};
+use super::{
+    cache::{CacheType, EntryMeta},
+    Cache,
+};
 
 pub trait Incr {
     fn incr(
@@ -26,6 +30,7 @@ impl Incr for Cache {
         value: Option<i32>,
         ignore_persistent: bool,
     ) -> bool {
+        let cluster = self.resolve_cluster(&cluster);
         let mut increment_value: Option<Vec<u8>> = None;
         if value.is_some() {
             let main_value = Option::Some(value.as_ref().unwrap().to_le_bytes().to_vec());
@@ -37,10 +42,15 @@ impl Incr for Cache {
 
         // Memory check and eviction
         {
-            let memory_handler = self.memory_handler.lock().unwrap();
-            if memory_handler.is_memory_limit_finished() {
+            // the guard is dropped before evicting, eviction locks the memory handler itself
+            let limit_reached = self
+                .memory_handler
+                .lock()
+                .unwrap()
+                .is_memory_limit_finished();
+            if limit_reached {
                 println!("Memory limit exceeded. Evicting entries...");
-                self.evict_entries();
+                self.evict_entries(&cluster, ignore_persistent);
                 if self.enable_log {
                     Logger::log_warn("Memory limit exceeded. Evicting entries").write_log_to_file();
                 }
@@ -63,22 +73,41 @@ impl Incr for Cache {
         }
 
         // Increment logic
+        let next_version = self.next_version();
         let mut store = self.store.lock().unwrap();
-        let cluster_store = store.entry(cluster.clone()).or_insert_with(HashMap::new);
+        if let Err(error) =
+            self.check_cluster_write(&mut store, &cluster, Some(&key), ignore_persistent)
+        {
+            if self.enable_log {
+                Logger::log_warn(&format!("Failed to increment value: {}", error.as_str()))
+                    .write_log_to_file();
+            }
+            return false;
+        }
+        let ttl = self.cluster_default_ttl(&cluster);
+        let cluster_store = store.entry(cluster.clone()).or_insert_with(BTreeMap::new);
 
-        let current_value = cluster_store
+        let entry = cluster_store
             .entry(key.clone())
-            .and_modify(|(existing_value, _, _, _)| {
+            .and_modify(|(existing_value, _, _, _, version, meta)| {
                 // Convert Vec<u8> to [u8; 4] and then to i32
                 let mut current_i32 = i32::from_le_bytes(existing_value[..4].try_into().unwrap());
                 current_i32 += vec_to_i32(increment_value.clone().unwrap()).unwrap();
 
                 // Update the value as Vec<u8>
                 *existing_value = current_i32.to_le_bytes().to_vec();
+                *version = next_version;
+                meta.touch_write();
             })
-            .or_insert((increment_value.clone().unwrap(), None, None, CacheType::Int))
-            .0
-            .clone();
+            .or_insert((
+                increment_value.clone().unwrap(),
+                ttl.map(|duration| Instant::now() + duration),
+                ttl,
+                CacheType::Int,
+                next_version,
+                EntryMeta::new(),
+            ));
+        let (current_value, expiration) = (entry.0.clone(), entry.1);
 
         // Memory management
         let mut memory_handler = self.memory_handler.lock().unwrap();
@@ -93,11 +122,97 @@ impl Incr for Cache {
             .write_log_to_file();
         }
 
-        if self.persistent && !ignore_persistent {
-            let command = format!("INCR {} {} {:?}", cluster, key, current_value);
-            persistent_Manager::write_to_persistent_file(&command);
-        }
+        self.persist(
+            set_int_record(&cluster, &key, &current_value, expiration),
+            ignore_persistent,
+        );
 
         true
     }
 }
+
+impl Cache {
+    // writes the counter as a whole with exactly the given ttl, replaying it gives the same value however often it runs.
+    // without a ttl the counter does not expire, the default ttl of the cluster is not applied
+    pub fn set_int(
+        &mut self,
+        cluster: &str,
+        key: &str,
+        value: i32,
+        ttl: Option<Duration>,
+        ignore_persistent: bool,
+    ) -> bool {
+        let cluster = &self.resolve_cluster(cluster);
+        if !self.has_memory_for_write(cluster, ignore_persistent) {
+            return false;
+        }
+        let next_version = self.next_version();
+        let mut store = self.store.lock().unwrap();
+        if self
+            .check_cluster_write(&mut store, cluster, Some(key), ignore_persistent)
+            .is_err()
+        {
+            return false;
+        }
+        let cluster_store = store
+            .entry(cluster.to_string())
+            .or_insert_with(BTreeMap::new);
+        let new_value = i32_to_vec(value);
+        let expiration = match cluster_store.get_mut(key) {
+            Some((existing_value, expiration, existing_ttl, cache_type, version, meta)) => {
+                *expiration = ttl.map(|duration| Instant::now() + duration);
+                *existing_ttl = ttl;
+                *existing_value = new_value.clone();
+                *cache_type = CacheType::Int;
+                *version = next_version;
+                meta.touch_write();
+                *expiration
+            }
+            None => {
+                let expiration = ttl.map(|duration| Instant::now() + duration);
+                self.memory_handler
+                    .lock()
+                    .unwrap()
+                    .add_memory(std::mem::size_of_val(&new_value));
+                cluster_store.insert(
+                    key.to_string(),
+                    (
+                        new_value.clone(),
+                        expiration,
+                        ttl,
+                        CacheType::Int,
+                        next_version,
+                        EntryMeta::new(),
+                    ),
+                );
+                expiration
+            }
+        };
+        self.persist(
+            set_int_record(cluster, key, &new_value, expiration),
+            ignore_persistent,
+        );
+        true
+    }
+}
+
+// counters are persisted with the value they ended up at, never with the step that got them there,
+// and with the deadline of their expiration like a SET
+pub fn set_int_record(
+    cluster: &str,
+    key: &str,
+    value: &[u8],
+    expiration: Option<Instant>,
+) -> PersistentRecord {
+    let record = PersistentRecord::new("SET_INT")
+        .arg(cluster)
+        .arg(key)
+        .arg(value);
+    match expiration {
+        Some(expiration) => record.arg(
+            record::deadline_millis(expiration.saturating_duration_since(Instant::now()))
+                .to_string(),
+        ),
+        None => record,
+    }
+}
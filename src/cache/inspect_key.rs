@@ -0,0 +1,52 @@
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use super::{cache::is_live, Cache};
+
+#[derive(Clone, Serialize, Debug)]
+pub struct KeyInfo {
+    pub value_type: String,
+    pub size: usize,
+    pub created_at: u64, // unix time in milliseconds
+    pub updated_at: u64,
+    pub last_access: u64,
+    pub access_count: u64,
+    pub ttl: Option<u64>, // remaining milliseconds, none when the key does not expire
+    pub version: u64,
+    pub tags: Vec<String>,
+}
+
+pub trait InspectKey {
+    fn inspect_key(&self, cluster: &str, key: &str) -> Option<KeyInfo>;
+}
+
+impl InspectKey for Cache {
+    // inspecting a key does not count as an access
+    fn inspect_key(&self, cluster: &str, key: &str) -> Option<KeyInfo> {
+        let cluster = &self.resolve_cluster(cluster);
+        let store = self.store.lock().unwrap();
+        let now = Instant::now();
+        let (value, expiration_time, _, cache_type, version, meta) = store
+            .get(cluster)
+            .and_then(|cluster_store| cluster_store.get(key))
+            .filter(|entry| is_live(entry, now))?;
+        Some(KeyInfo {
+            value_type: cache_type.as_str().to_string(),
+            size: value.len(),
+            created_at: unix_millis(meta.created_at),
+            updated_at: unix_millis(meta.updated_at),
+            last_access: unix_millis(meta.last_access),
+            access_count: meta.access_count,
+            ttl: expiration_time.map(|exp| exp.saturating_duration_since(now).as_millis() as u64),
+            version: *version,
+            tags: meta.tags.clone(),
+        })
+    }
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
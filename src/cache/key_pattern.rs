@@ -0,0 +1,82 @@
+use regex::Regex;
+
+#[derive(Clone, Debug)]
+pub struct KeyPattern {
+    source: String,
+    regex: Regex,
+}
+
+impl KeyPattern {
+    // glob syntax: `*` any run of characters, `?` one character, `[abc]`, `[a-z]`, `[!a]` sets, `\` escapes
+    pub fn glob(pattern: &str) -> Result<Self, String> {
+        let mut expression = String::from("^");
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' => expression.push_str(".*"),
+                '?' => expression.push('.'),
+                '\\' => match chars.next() {
+                    Some(escaped) => expression.push_str(&regex::escape(&escaped.to_string())),
+                    None => expression.push_str(&regex::escape("\\")),
+                },
+                '[' => {
+                    let mut class = String::from("[");
+                    if let Some(&negate) = chars.peek() {
+                        if negate == '!' || negate == '^' {
+                            class.push('^');
+                            chars.next();
+                        }
+                    }
+                    let mut closed = false;
+                    for member in chars.by_ref() {
+                        if member == ']' {
+                            closed = true;
+                            break;
+                        }
+                        if member == '\\' || member == '[' || member == '&' || member == '~' {
+                            class.push('\\');
+                        }
+                        class.push(member);
+                    }
+                    if !closed {
+                        return Err(format!("unterminated [ in pattern {}", pattern));
+                    }
+                    class.push(']');
+                    expression.push_str(&class);
+                }
+                _ => expression.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        expression.push('$');
+        Self::build(pattern, &expression)
+    }
+
+    pub fn regex(pattern: &str) -> Result<Self, String> {
+        Self::build(pattern, pattern)
+    }
+
+    pub fn parse(pattern: &str, is_regex: bool) -> Result<Self, String> {
+        if is_regex {
+            Self::regex(pattern)
+        } else {
+            Self::glob(pattern)
+        }
+    }
+
+    fn build(source: &str, expression: &str) -> Result<Self, String> {
+        Regex::new(expression)
+            .map(|regex| KeyPattern {
+                source: source.to_string(),
+                regex,
+            })
+            .map_err(|error| format!("invalid pattern {}: {}", source, error))
+    }
+
+    pub fn is_match(&self, name: &str) -> bool {
+        self.regex.is_match(name)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
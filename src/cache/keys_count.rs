@@ -6,6 +6,7 @@ pub trait KeysCount {
 
 impl KeysCount for Cache {
     fn keys_count(&self, cluster: &str) -> i64 {
+        let cluster = &self.resolve_cluster(cluster);
         let store = self.store.lock().unwrap();
         let key_count = store.get(cluster).map(|k| k.keys().len() as i64);
         key_count.unwrap_or(-1)
@@ -1,19 +1,45 @@
+pub mod append;
 pub mod cache;
 pub mod clear_all;
 pub mod clear_cluster;
 pub mod clear_expired;
+pub mod cluster_alias;
+pub mod cluster_config;
+pub mod conditional_set;
+pub mod copy_key;
 pub mod decr;
 pub mod delete;
+pub mod distributed_lock;
+pub mod drop_cluster;
+pub mod dump;
 pub mod get;
 pub mod get_all_clusters;
 pub mod get_cluster_keys;
+pub mod get_del;
+pub mod get_range;
+pub mod get_set;
 pub mod incr;
+pub mod inspect_key;
+pub mod key_pattern;
 pub mod move_cluster;
 pub mod move_del_cluster;
+pub mod move_key;
+pub mod point_in_time;
+pub mod rate_limit;
+pub mod rename_key;
+pub mod scan;
+pub mod script;
 pub mod set;
 pub mod set_cluster;
+pub mod set_range;
+pub mod snapshot;
+pub mod str_len;
+pub mod string_value;
+pub mod tag_index;
+pub mod transaction;
 pub use cache::Cache;
 pub mod exist_key;
+pub mod expire_cluster;
 pub mod expire_key;
 pub mod keys_count;
 pub mod type_of_key;
@@ -1,52 +1,165 @@
-use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Instant;
 
-use crate::logger::logger_manager::Logger;
+use serde::{Deserialize, Serialize};
 
-use super::{set::Set, Cache};
+use crate::{logger::logger_manager::Logger, persistent::record::PersistentRecord};
+
+use super::{
+    cache::{is_live, CacheError, Store},
+    Cache,
+};
+
+// what to do with keys that already exist in the destination cluster
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ClusterWritePolicy {
+    #[default]
+    Overwrite,
+    SkipExisting,
+}
+
+impl ClusterWritePolicy {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ClusterWritePolicy::Overwrite => "overwrite",
+            ClusterWritePolicy::SkipExisting => "skip_existing",
+        }
+    }
+}
+
+impl FromStr for ClusterWritePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "overwrite" => Ok(ClusterWritePolicy::Overwrite),
+            "skip_existing" => Ok(ClusterWritePolicy::SkipExisting),
+            _ => Err(format!("invalid cluster write policy {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct ClusterCopyResult {
+    pub copied: usize,
+    pub skipped: usize,
+    // keys the destination did not take, a move leaves them in the source
+    pub skipped_keys: Vec<String>,
+}
 
 pub trait CopyCluster {
-    fn copy_cluster(&mut self, src_cluster: &str, desc_cluster: &str) -> bool;
+    fn copy_cluster(
+        &mut self,
+        src_cluster: &str,
+        desc_cluster: &str,
+        policy: ClusterWritePolicy,
+        ignore_persistent: bool,
+    ) -> Result<ClusterCopyResult, CacheError>;
 }
 
 impl CopyCluster for Cache {
-    fn copy_cluster(&mut self, src_cluster: &str, desc_cluster: &str) -> bool {
-        let src_data = {
-            let store = self.store.lock().unwrap();
-            store.get(src_cluster).cloned()
+    fn copy_cluster(
+        &mut self,
+        src_cluster: &str,
+        desc_cluster: &str,
+        policy: ClusterWritePolicy,
+        ignore_persistent: bool,
+    ) -> Result<ClusterCopyResult, CacheError> {
+        let src_cluster = &self.resolve_cluster(src_cluster);
+        let desc_cluster = &self.resolve_cluster(desc_cluster);
+        if !self.has_memory_for_write(desc_cluster, ignore_persistent) {
+            return Err(CacheError::MemoryLimitExceeded);
+        }
+
+        let result = {
+            let mut store = self.store.lock().unwrap();
+            self.copy_cluster_entries(
+                &mut store,
+                src_cluster,
+                desc_cluster,
+                policy,
+                ignore_persistent,
+            )?
         };
 
-        if let Some(src_data) = src_data {
-            let mut success = true;
-            for (key, value) in src_data.iter() {
-                let (val, _, _, _) = value;
-
-                if !self.set(
-                    desc_cluster.to_string(),
-                    key.to_string(),
-                    val.to_vec(),
-                    None,
-                    true,
-                ) {
-                    Logger::log_error(&format!("Failed to set key {}", key)).write_log_to_file();
-                    success = false;
-                }
-            }
+        Logger::log_info_data(&format!(
+            "Cluster: {} copied to cluster: {}",
+            src_cluster, desc_cluster
+        ))
+        .write_log_to_file();
+        self.persist(
+            PersistentRecord::new("COPY_CLUSTER")
+                .arg(src_cluster)
+                .arg(desc_cluster)
+                .arg(policy.as_str()),
+            ignore_persistent,
+        );
+        Ok(result)
+    }
+}
 
-            if success {
-                Logger::log_info_data(&format!(
-                    "Cluster: {} moved to cluster: {}",
-                    src_cluster, desc_cluster
-                ))
-                .write_log_to_file();
+impl Cache {
+    // copies every live entry with its type and expiration, all under one store lock
+    pub fn copy_cluster_entries(
+        &self,
+        store: &mut Store,
+        src_cluster: &str,
+        desc_cluster: &str,
+        policy: ClusterWritePolicy,
+        ignore_persistent: bool,
+    ) -> Result<ClusterCopyResult, CacheError> {
+        let now = Instant::now();
+        let src_entries: Vec<_> = store
+            .get(src_cluster)
+            .ok_or(CacheError::ClusterNotFound)?
+            .iter()
+            .filter(|(_, entry)| is_live(entry, now))
+            .map(|(key, entry)| (key.clone(), entry.clone()))
+            .collect();
+        if src_cluster == desc_cluster {
+            return Ok(ClusterCopyResult {
+                copied: 0,
+                skipped: src_entries.len(),
+                skipped_keys: src_entries.into_iter().map(|(key, _)| key).collect(),
+            });
+        }
 
-                let mut store = self.store.lock().unwrap();
+        self.check_cluster_write(store, desc_cluster, None, ignore_persistent)?;
+        let mut result = ClusterCopyResult {
+            copied: 0,
+            skipped: 0,
+            skipped_keys: Vec::new(),
+        };
+        for (key, mut entry) in src_entries {
+            let exists = store
+                .get(desc_cluster)
+                .and_then(|desc_store| desc_store.get(&key))
+                .map_or(false, |entry| is_live(entry, now));
+            let skip = exists && policy == ClusterWritePolicy::SkipExisting;
+            if skip
+                || self
+                    .check_cluster_write(store, desc_cluster, Some(&key), ignore_persistent)
+                    .is_err()
+            {
+                result.skipped += 1;
+                result.skipped_keys.push(key);
+                continue;
             }
-
-            success
-        } else {
-            Logger::log_error("Cluster not found in store for moving cluster values")
-                .write_log_to_file();
-            false
+            entry.4 = self.next_version();
+            entry.5 = entry.5.copied();
+            let mut memory_handler = self.memory_handler.lock().unwrap();
+            memory_handler.add_memory(std::mem::size_of_val(&entry.0));
+            let desc_store = store.entry(desc_cluster.to_string()).or_default();
+            // the replaced entry leaves the tag index before the new one joins it, they may share tags
+            if let Some(replaced) = desc_store.remove(&key) {
+                self.unindex_entry_tags(desc_cluster, &key, &replaced);
+                memory_handler.delete_memory(std::mem::size_of_val(&replaced.0));
+            }
+            self.index_entry_tags(desc_cluster, &key, &entry);
+            desc_store.insert(key, entry);
+            result.copied += 1;
         }
+        Ok(result)
     }
 }
@@ -1,56 +1,82 @@
-use crate::logger::logger_manager::Logger;
+use std::collections::HashSet;
 
-use super::{set::Set, Cache};
+use crate::{logger::logger_manager::Logger, persistent::record::PersistentRecord};
+
+use super::{
+    cache::CacheError,
+    move_cluster::{ClusterCopyResult, ClusterWritePolicy},
+    Cache,
+};
 
 pub trait MoveCluster {
-    fn move_cluster(&mut self, src_cluster: &str, set_cluster: &str) -> bool;
+    fn move_cluster(
+        &mut self,
+        src_cluster: &str,
+        desc_cluster: &str,
+        policy: ClusterWritePolicy,
+        ignore_persistent: bool,
+    ) -> Result<ClusterCopyResult, CacheError>;
 }
 
 impl MoveCluster for Cache {
-    fn move_cluster(&mut self, src_cluster: &str, desc_cluster: &str) -> bool {
-        let src_data = {
-            let store = self.store.lock().unwrap();
-            store.get(src_cluster).cloned()
-        };
-
-        if let Some(src_data) = src_data {
-            let mut success = true;
-            for (key, value) in src_data.iter() {
-                let (val, _, _, _) = value;
+    fn move_cluster(
+        &mut self,
+        src_cluster: &str,
+        desc_cluster: &str,
+        policy: ClusterWritePolicy,
+        ignore_persistent: bool,
+    ) -> Result<ClusterCopyResult, CacheError> {
+        let src_cluster = &self.resolve_cluster(src_cluster);
+        let desc_cluster = &self.resolve_cluster(desc_cluster);
+        if !self.has_memory_for_write(desc_cluster, ignore_persistent) {
+            return Err(CacheError::MemoryLimitExceeded);
+        }
 
-                if !self.set(
-                    desc_cluster.to_string(),
-                    key.to_string(),
-                    val.to_vec(),
-                    None,
-                    true,
-                ) {
-                    Logger::log_error(&format!("Failed to set key {}", key)).write_log_to_file();
-                    success = false;
+        let result = {
+            let mut store = self.store.lock().unwrap();
+            self.check_cluster_write(&mut store, src_cluster, None, ignore_persistent)?;
+            let result = self.copy_cluster_entries(
+                &mut store,
+                src_cluster,
+                desc_cluster,
+                policy,
+                ignore_persistent,
+            )?;
+            if src_cluster != desc_cluster {
+                // only moved keys leave the source, the ones the destination did not take stay
+                let skipped: HashSet<&String> = result.skipped_keys.iter().collect();
+                if let Some(src_store) = store.get_mut(src_cluster) {
+                    let mut freed = 0;
+                    src_store.retain(|key, entry| {
+                        let keep = skipped.contains(key);
+                        if !keep {
+                            self.unindex_entry_tags(src_cluster, key, entry);
+                            freed += std::mem::size_of_val(&entry.0);
+                        }
+                        keep
+                    });
+                    self.memory_handler.lock().unwrap().delete_memory(freed);
+                    if src_store.is_empty() {
+                        store.remove(src_cluster);
+                        self.remove_cluster_settings(src_cluster);
+                    }
                 }
             }
+            result
+        };
 
-            if success {
-                Logger::log_info_data(&format!(
-                    "Cluster: {} moved to cluster: {}",
-                    src_cluster, desc_cluster
-                ))
-                .write_log_to_file();
-
-                let mut store = self.store.lock().unwrap();
-                store.remove(src_cluster);
-                Logger::log_info_data(&format!(
-                    "Cluster: {} remove after moved to cluster: {}",
-                    src_cluster, desc_cluster
-                ))
-                .write_log_to_file();
-            }
-
-            success
-        } else {
-            Logger::log_error("Cluster not found in store for moving cluster values")
-                .write_log_to_file();
-            false
-        }
+        Logger::log_info_data(&format!(
+            "Cluster: {} moved to cluster: {}",
+            src_cluster, desc_cluster
+        ))
+        .write_log_to_file();
+        self.persist(
+            PersistentRecord::new("MOVE_CLUSTER")
+                .arg(src_cluster)
+                .arg(desc_cluster)
+                .arg(policy.as_str()),
+            ignore_persistent,
+        );
+        Ok(result)
     }
 }
@@ -0,0 +1,85 @@
+use std::time::Instant;
+
+use crate::{logger::logger_manager::Logger, persistent::record::PersistentRecord};
+
+use super::{
+    cache::{is_live, CacheError},
+    Cache,
+};
+
+pub trait MoveKey {
+    // Ok(false) when the key already exists in the destination cluster and `replace` is not set
+    fn move_key(
+        &mut self,
+        src_cluster: &str,
+        key: &str,
+        desc_cluster: &str,
+        replace: bool,
+        ignore_persistent: bool,
+    ) -> Result<bool, CacheError>;
+}
+
+impl MoveKey for Cache {
+    fn move_key(
+        &mut self,
+        src_cluster: &str,
+        key: &str,
+        desc_cluster: &str,
+        replace: bool,
+        ignore_persistent: bool,
+    ) -> Result<bool, CacheError> {
+        let src_cluster = &self.resolve_cluster(src_cluster);
+        let desc_cluster = &self.resolve_cluster(desc_cluster);
+        let next_version = self.next_version();
+        let mut store = self.store.lock().unwrap();
+        let now = Instant::now();
+        let source_exists = store
+            .get(src_cluster)
+            .and_then(|cluster_store| cluster_store.get(key))
+            .map_or(false, |entry| is_live(entry, now));
+        if !source_exists {
+            return Err(CacheError::KeyNotFound);
+        }
+        if src_cluster == desc_cluster {
+            return Ok(true);
+        }
+        let target_exists = store
+            .get(desc_cluster)
+            .and_then(|cluster_store| cluster_store.get(key))
+            .map_or(false, |entry| is_live(entry, now));
+        if target_exists && !replace {
+            return Ok(false);
+        }
+        self.check_cluster_write(&mut store, src_cluster, None, ignore_persistent)?;
+        self.check_cluster_write(&mut store, desc_cluster, Some(key), ignore_persistent)?;
+
+        // value, type and expiration move along with the key
+        let mut entry = store.get_mut(src_cluster).unwrap().remove(key).unwrap();
+        entry.4 = next_version;
+        entry.5.touch_write();
+        self.unindex_entry_tags(src_cluster, key, &entry);
+        let desc_store = store.entry(desc_cluster.to_string()).or_default();
+        // the replaced entry leaves the tag index before the new one joins it, they may share tags
+        if let Some(replaced) = desc_store.remove(key) {
+            self.unindex_entry_tags(desc_cluster, key, &replaced);
+            self.memory_handler
+                .lock()
+                .unwrap()
+                .delete_memory(std::mem::size_of_val(&replaced.0));
+        }
+        self.index_entry_tags(desc_cluster, key, &entry);
+        desc_store.insert(key.to_string(), entry);
+
+        if self.enable_log {
+            Logger::log_info("key moved").write_log_to_file();
+        }
+        self.persist(
+            PersistentRecord::new("MOVE")
+                .arg(src_cluster)
+                .arg(key)
+                .arg(desc_cluster),
+            ignore_persistent,
+        );
+        Ok(true)
+    }
+}
@@ -0,0 +1,91 @@
+use chrono::DateTime;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+use crate::memory_handling::memory_handling::MemoryHandler;
+
+use super::{
+    cache::RecoverySummary,
+    clear_cluster::ClearCluster,
+    dump::{DumpExport, DumpImporter, ImportMode, ImportSummary},
+    set_cluster::SetCluster,
+    Cache,
+};
+
+#[derive(Serialize)]
+pub struct PointInTimeResult {
+    pub until: u64,
+    pub replay: RecoverySummary,
+    pub restore: ImportSummary,
+}
+
+// a point in time is either unix milliseconds or an RFC 3339 date like 2024-05-01T10:30:00+02:00
+pub fn parse_point_in_time(value: &str) -> Result<u64, String> {
+    if let Ok(millis) = value.parse::<u64>() {
+        return Ok(millis);
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map_err(|_| {
+            format!(
+                "invalid point in time {}, use unix milliseconds or an RFC 3339 date",
+                value
+            )
+        })
+        .and_then(|time| {
+            u64::try_from(time.timestamp_millis())
+                .map_err(|_| format!("point in time {} is before 1970", value))
+        })
+}
+
+pub trait PointInTime {
+    fn recovery_cache(&self) -> Cache;
+    fn restore_clusters(
+        &mut self,
+        recovered: &Cache,
+        clusters: &[String],
+    ) -> Result<ImportSummary, String>;
+}
+
+impl PointInTime for Cache {
+    // an empty cache with the same settings to replay the history into, nothing written to it is persisted.
+    // it counts its memory on its own so the replay neither inflates the usage of the live cache nor is cut short by it
+    fn recovery_cache(&self) -> Cache {
+        Cache::new(
+            self.port,
+            Arc::new(Mutex::new(MemoryHandler::new())),
+            self.evict_type,
+            self.enable_log,
+            false,
+            self.creds_manager.clone(),
+            self.script_limits,
+        )
+    }
+
+    // replaces the content of the given clusters, or of every recovered cluster when none are given,
+    // with what `recovered` holds. the writes are persisted like any other
+    fn restore_clusters(
+        &mut self,
+        recovered: &Cache,
+        clusters: &[String],
+    ) -> Result<ImportSummary, String> {
+        let mut export = DumpExport::new(recovered, clusters)
+            .map_err(|error| format!("{} at that point in time", error))?;
+        let mut importer = DumpImporter::new(ImportMode::Merge);
+        // clusters are cleared up front so one that was empty back then ends up empty as well
+        for cluster in export.clusters() {
+            let cluster = self.resolve_cluster(cluster);
+            self.set_cluster(cluster.clone(), false);
+            self.clear_cluster(&cluster, false);
+            importer.summary.replaced_clusters.push(cluster);
+        }
+        while let Some(entries) = export.next_page(recovered) {
+            for entry in entries {
+                let name = format!("{} {}", entry.cluster, entry.key);
+                if let Err(error) = importer.import(self, entry) {
+                    importer.report(format!("{}: {}", name, error));
+                }
+            }
+        }
+        Ok(importer.summary)
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::Cache;
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitAlgorithm {
+    // `limit` tokens, refilled continuously over `window`
+    TokenBucket,
+    // at most `limit` requests within any `window`
+    SlidingWindow,
+}
+
+#[derive(Clone, Debug)]
+pub enum RateLimitState {
+    TokenBucket {
+        tokens: f64,
+        last_refill: Instant,
+        window: Duration,
+    },
+    SlidingWindow {
+        // hits made at the same instant share one entry with the cost they added up to
+        hits: VecDeque<(Instant, u64)>,
+        used: u64,
+        window: Duration,
+    },
+}
+
+impl RateLimitState {
+    fn new(algorithm: RateLimitAlgorithm, limit: u64, window: Duration, now: Instant) -> Self {
+        match algorithm {
+            RateLimitAlgorithm::TokenBucket => RateLimitState::TokenBucket {
+                tokens: limit as f64,
+                last_refill: now,
+                window,
+            },
+            RateLimitAlgorithm::SlidingWindow => RateLimitState::SlidingWindow {
+                hits: VecDeque::new(),
+                used: 0,
+                window,
+            },
+        }
+    }
+
+    fn matches(&self, algorithm: RateLimitAlgorithm, window: Duration) -> bool {
+        match self {
+            RateLimitState::TokenBucket { window: w, .. } => {
+                algorithm == RateLimitAlgorithm::TokenBucket && *w == window
+            }
+            RateLimitState::SlidingWindow { window: w, .. } => {
+                algorithm == RateLimitAlgorithm::SlidingWindow && *w == window
+            }
+        }
+    }
+
+    fn is_stale(&self, now: Instant) -> bool {
+        match self {
+            RateLimitState::TokenBucket {
+                last_refill,
+                window,
+                ..
+            } => now.duration_since(*last_refill) >= *window,
+            RateLimitState::SlidingWindow { hits, window, .. } => hits
+                .back()
+                .map_or(true, |(last, _)| now.duration_since(*last) >= *window),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct RateLimitResult {
+    pub allowed: bool,
+    pub remaining: u64,
+    pub retry_after: u64, // milliseconds until the request would be allowed, 0 when allowed
+}
+
+pub trait RateLimit {
+    fn rate_limit(
+        &self,
+        cluster: &str,
+        key: &str,
+        algorithm: RateLimitAlgorithm,
+        limit: u64,
+        window: Duration,
+        cost: u64,
+    ) -> RateLimitResult;
+}
+
+impl RateLimit for Cache {
+    fn rate_limit(
+        &self,
+        cluster: &str,
+        key: &str,
+        algorithm: RateLimitAlgorithm,
+        limit: u64,
+        window: Duration,
+        cost: u64,
+    ) -> RateLimitResult {
+        let mut rate_limits = self.rate_limits.lock().unwrap();
+        let now = Instant::now();
+
+        let state_key = (cluster.to_string(), key.to_string());
+        // a new key, or a key asked with another algorithm or window, starts over
+        let matches_request = rate_limits
+            .get(&state_key)
+            .map_or(false, |state| state.matches(algorithm, window));
+        if !matches_request {
+            rate_limits.insert(
+                state_key.clone(),
+                RateLimitState::new(algorithm, limit, window, now),
+            );
+        }
+        let state = rate_limits.get_mut(&state_key).unwrap();
+
+        match state {
+            RateLimitState::TokenBucket {
+                tokens,
+                last_refill,
+                window,
+            } => {
+                let refill_per_ms = limit as f64 / window.as_millis().max(1) as f64;
+                let elapsed = now.duration_since(*last_refill).as_millis() as f64;
+                *tokens = (*tokens + elapsed * refill_per_ms).min(limit as f64);
+                *last_refill = now;
+
+                let cost = cost as f64;
+                if *tokens >= cost {
+                    *tokens -= cost;
+                    RateLimitResult {
+                        allowed: true,
+                        remaining: tokens.floor() as u64,
+                        retry_after: 0,
+                    }
+                } else {
+                    let missing = cost - *tokens;
+                    RateLimitResult {
+                        allowed: false,
+                        remaining: tokens.floor() as u64,
+                        retry_after: if cost > limit as f64 {
+                            window.as_millis() as u64
+                        } else {
+                            (missing / refill_per_ms).ceil() as u64
+                        },
+                    }
+                }
+            }
+            RateLimitState::SlidingWindow { hits, used, window } => {
+                while let Some((oldest, count)) = hits.front() {
+                    if now.duration_since(*oldest) >= *window {
+                        *used = used.saturating_sub(*count);
+                        hits.pop_front();
+                    } else {
+                        break;
+                    }
+                }
+
+                if let Some(total) = used.checked_add(cost).filter(|total| *total <= limit) {
+                    match hits.back_mut() {
+                        Some((last, count)) if *last == now => *count += cost,
+                        _ => hits.push_back((now, cost)),
+                    }
+                    *used = total;
+                    RateLimitResult {
+                        allowed: true,
+                        remaining: limit - total,
+                        retry_after: 0,
+                    }
+                } else {
+                    // wait until enough of the oldest hits have left the window
+                    let needed = used.saturating_add(cost) - limit;
+                    let mut freed = 0;
+                    let retry_after = hits
+                        .iter()
+                        .find(|(_, count)| {
+                            freed += count;
+                            freed >= needed
+                        })
+                        .and_then(|(hit, _)| hit.checked_add(*window))
+                        .map_or(*window, |free_at| free_at.saturating_duration_since(now));
+                    RateLimitResult {
+                        allowed: false,
+                        remaining: limit.saturating_sub(*used),
+                        retry_after: retry_after.as_millis() as u64,
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Cache {
+    // fully refilled buckets and empty windows hold no information, the expiry job drops them
+    // so idle keys do not pile up
+    pub fn clear_stale_rate_limits(&self) {
+        let now = Instant::now();
+        self.rate_limits
+            .lock()
+            .unwrap()
+            .retain(|_, state| !state.is_stale(now));
+    }
+}
@@ -0,0 +1,81 @@
+use std::time::Instant;
+
+use crate::{logger::logger_manager::Logger, persistent::record::PersistentRecord};
+
+use super::{
+    cache::{is_live, CacheError},
+    Cache,
+};
+
+pub trait RenameKey {
+    // Ok(false) when `nx` is set and the new key already exists
+    fn rename_key(
+        &mut self,
+        cluster: &str,
+        key: &str,
+        new_key: &str,
+        nx: bool,
+        ignore_persistent: bool,
+    ) -> Result<bool, CacheError>;
+}
+
+impl RenameKey for Cache {
+    fn rename_key(
+        &mut self,
+        cluster: &str,
+        key: &str,
+        new_key: &str,
+        nx: bool,
+        ignore_persistent: bool,
+    ) -> Result<bool, CacheError> {
+        let cluster = &self.resolve_cluster(cluster);
+        let next_version = self.next_version();
+        let mut store = self.store.lock().unwrap();
+        self.check_cluster_write(&mut store, cluster, None, ignore_persistent)?;
+        let now = Instant::now();
+        let cluster_store = store.get_mut(cluster).ok_or(CacheError::KeyNotFound)?;
+        if !cluster_store
+            .get(key)
+            .map_or(false, |entry| is_live(entry, now))
+        {
+            return Err(CacheError::KeyNotFound);
+        }
+        if key == new_key {
+            return Ok(true);
+        }
+        let target_exists = cluster_store
+            .get(new_key)
+            .map_or(false, |entry| is_live(entry, now));
+        if nx && target_exists {
+            return Ok(false);
+        }
+
+        // value, type and expiration move along with the key
+        let mut entry = cluster_store.remove(key).unwrap();
+        entry.4 = next_version;
+        entry.5.touch_write();
+        self.unindex_entry_tags(cluster, key, &entry);
+        // the replaced entry leaves the tag index before the new one joins it, they may share tags
+        if let Some(replaced) = cluster_store.remove(new_key) {
+            self.unindex_entry_tags(cluster, new_key, &replaced);
+            self.memory_handler
+                .lock()
+                .unwrap()
+                .delete_memory(std::mem::size_of_val(&replaced.0));
+        }
+        self.index_entry_tags(cluster, new_key, &entry);
+        cluster_store.insert(new_key.to_string(), entry);
+
+        if self.enable_log {
+            Logger::log_info("key renamed").write_log_to_file();
+        }
+        self.persist(
+            PersistentRecord::new("RENAME")
+                .arg(cluster)
+                .arg(key)
+                .arg(new_key),
+            ignore_persistent,
+        );
+        Ok(true)
+    }
+}
@@ -0,0 +1,132 @@
+use std::ops::Bound;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use super::{key_pattern::KeyPattern, Cache};
+
+pub const DEFAULT_SCAN_COUNT: usize = 100;
+pub const MAX_SCAN_COUNT: usize = 10_000;
+
+#[derive(Clone, Serialize, Debug)]
+pub struct ScanResult {
+    pub items: Vec<String>,
+    pub cursor: Option<String>, // None once the scan is complete
+}
+
+// `count` bounds how many entries one call examines; with a pattern a page may hold fewer matches
+pub trait Scan {
+    fn scan_keys(
+        &self,
+        cluster: &str,
+        cursor: Option<&str>,
+        count: usize,
+        pattern: Option<&KeyPattern>,
+    ) -> Result<ScanResult, String>;
+    fn scan_clusters(
+        &self,
+        cursor: Option<&str>,
+        count: usize,
+        pattern: Option<&KeyPattern>,
+    ) -> Result<ScanResult, String>;
+}
+
+impl Scan for Cache {
+    fn scan_keys(
+        &self,
+        cluster: &str,
+        cursor: Option<&str>,
+        count: usize,
+        pattern: Option<&KeyPattern>,
+    ) -> Result<ScanResult, String> {
+        let cluster = &self.resolve_cluster(cluster);
+        let after = decode_cursor(cursor)?;
+        let count = count.clamp(1, MAX_SCAN_COUNT);
+        let store = self.store.lock().unwrap();
+        let cluster_store = store
+            .get(cluster)
+            .ok_or_else(|| format!("cluster {} not found", cluster))?;
+
+        let now = Instant::now();
+        let mut range = cluster_store.range::<str, _>(scan_range(&after));
+        let page: Vec<(&String, bool)> = range
+            .by_ref()
+            .take(count)
+            .map(|(key, (_, expiration_time, _, _, _, _))| {
+                let is_live = expiration_time.map_or(true, |exp| exp > now);
+                (
+                    key,
+                    is_live && pattern.map_or(true, |pattern| pattern.is_match(key)),
+                )
+            })
+            .collect();
+
+        let has_more = range.next().is_some();
+        Ok(ScanResult {
+            cursor: page
+                .last()
+                .filter(|_| has_more)
+                .map(|(key, _)| encode_cursor(key)),
+            items: page
+                .into_iter()
+                .filter(|(_, is_included)| *is_included)
+                .map(|(key, _)| key.clone())
+                .collect(),
+        })
+    }
+
+    fn scan_clusters(
+        &self,
+        cursor: Option<&str>,
+        count: usize,
+        pattern: Option<&KeyPattern>,
+    ) -> Result<ScanResult, String> {
+        let after = decode_cursor(cursor)?;
+        let count = count.clamp(1, MAX_SCAN_COUNT);
+        let store = self.store.lock().unwrap();
+
+        let mut range = store.range::<str, _>(scan_range(&after));
+        let page: Vec<&String> = range
+            .by_ref()
+            .take(count)
+            .map(|(cluster, _)| cluster)
+            .collect();
+
+        let has_more = range.next().is_some();
+        Ok(ScanResult {
+            cursor: page
+                .last()
+                .filter(|_| has_more)
+                .map(|cluster| encode_cursor(cluster)),
+            items: page
+                .into_iter()
+                .filter(|cluster| pattern.map_or(true, |pattern| pattern.is_match(cluster)))
+                .cloned()
+                .collect(),
+        })
+    }
+}
+
+// the cursor is the last name returned, so entries added or removed between pages never shift the scan
+fn scan_range(after: &Option<String>) -> (Bound<&str>, Bound<&str>) {
+    match after {
+        Some(name) => (Bound::Excluded(name.as_str()), Bound::Unbounded),
+        None => (Bound::Unbounded, Bound::Unbounded),
+    }
+}
+
+fn encode_cursor(name: &str) -> String {
+    format!("c{}", hex::encode(name.as_bytes()))
+}
+
+fn decode_cursor(cursor: Option<&str>) -> Result<Option<String>, String> {
+    match cursor {
+        None | Some("") | Some("0") => Ok(None),
+        Some(cursor) => cursor
+            .strip_prefix('c')
+            .and_then(|encoded| hex::decode(encoded).ok())
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .map(Some)
+            .ok_or_else(|| "invalid scan cursor".to_string()),
+    }
+}
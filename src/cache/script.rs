@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Scope, AST};
+use sha1::{Digest, Sha1};
+
+use crate::{convert::vec_to_i32, logger::logger_manager::Logger};
+
+use super::{
+    cache::CacheType, decr::Decr, delete::Delete, exist_key::KeyExists, expire_key::ExpireKey,
+    get::Get, incr::Incr, set::Set, Cache,
+};
+
+// loaded scripts by their sha1, compiled once
+pub type ScriptCache = HashMap<String, AST>;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ScriptLimits {
+    pub max_operations: u64,
+    pub timeout: Duration,
+}
+
+pub trait Script {
+    fn script_load(&self, source: &str) -> Result<String, String>;
+    fn script_exists(&self, sha: &str) -> bool;
+    fn script_eval(&mut self, sha: &str, args: Vec<String>) -> Result<serde_json::Value, String>;
+}
+
+impl Script for Cache {
+    fn script_load(&self, source: &str) -> Result<String, String> {
+        let sha = script_sha(source);
+        if self.scripts.lock().unwrap().contains_key(&sha) {
+            return Ok(sha);
+        }
+
+        let ast = Engine::new()
+            .compile(source)
+            .map_err(|error| format!("script compile error: {}", error))?;
+        self.scripts.lock().unwrap().insert(sha.clone(), ast);
+        if self.enable_log {
+            Logger::log_info_data(&format!("script {} loaded", sha)).write_log_to_file();
+        }
+        Ok(sha)
+    }
+
+    fn script_exists(&self, sha: &str) -> bool {
+        self.scripts.lock().unwrap().contains_key(sha)
+    }
+
+    fn script_eval(&mut self, sha: &str, args: Vec<String>) -> Result<serde_json::Value, String> {
+        let ast = match self.scripts.lock().unwrap().get(sha) {
+            Some(ast) => ast.clone(),
+            None => return Err(format!("no script loaded with sha {}", sha)),
+        };
+
+        // the caller holds the cache lock for the whole run, so the script sees no interleaved writes
+        let engine = self.script_engine();
+        let mut scope = Scope::new();
+        let args: Array = args.into_iter().map(Dynamic::from).collect();
+        scope.push_constant("ARGS", args);
+
+        let result = engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, &ast)
+            .map_err(|error| format!("script error: {}", error))?;
+        serde_json::to_value(&result).map_err(|error| format!("script result error: {}", error))
+    }
+}
+
+impl Cache {
+    fn script_engine(&self) -> Engine {
+        let mut engine = Engine::new();
+        let limits = self.script_limits;
+        let started = Instant::now();
+
+        engine.set_max_operations(limits.max_operations);
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(1_048_576);
+        engine.set_max_array_size(100_000);
+        engine.set_max_map_size(100_000);
+        engine.disable_symbol("eval");
+        engine.on_print(|_| {});
+        engine.on_debug(|_, _, _| {});
+        engine.on_progress(move |_| {
+            if started.elapsed() > limits.timeout {
+                Some(Dynamic::from("script timed out"))
+            } else {
+                None
+            }
+        });
+
+        let cache = Arc::new(Mutex::new(self.clone()));
+
+        let handle = cache.clone();
+        engine.register_fn("get", move |cluster: &str, key: &str| -> Dynamic {
+            let result = handle.lock().unwrap().get(cluster, key);
+            match (result.value, result.value_type) {
+                (Some(value), Some(CacheType::Int)) => vec_to_i32(value)
+                    .map(|number| Dynamic::from(number as i64))
+                    .unwrap_or(Dynamic::UNIT),
+                (Some(value), _) => Dynamic::from(String::from_utf8_lossy(&value).to_string()),
+                _ => Dynamic::UNIT,
+            }
+        });
+
+        let handle = cache.clone();
+        engine.register_fn(
+            "set",
+            move |cluster: &str, key: &str, value: &str| -> bool {
+                handle.lock().unwrap().set(
+                    cluster.to_string(),
+                    key.to_string(),
+                    value.as_bytes().to_vec(),
+                    None,
+                    false,
+                )
+            },
+        );
+
+        let handle = cache.clone();
+        engine.register_fn(
+            "set",
+            move |cluster: &str, key: &str, value: &str, ttl: i64| -> bool {
+                handle.lock().unwrap().set(
+                    cluster.to_string(),
+                    key.to_string(),
+                    value.as_bytes().to_vec(),
+                    Some(Duration::from_millis(ttl.max(0) as u64)),
+                    false,
+                )
+            },
+        );
+
+        let handle = cache.clone();
+        engine.register_fn(
+            "incr",
+            move |cluster: &str, key: &str, value: i64| -> Result<Dynamic, Box<EvalAltResult>> {
+                let value = to_i32(value)?;
+                let mut cache = handle.lock().unwrap();
+                cache.incr(cluster.to_string(), key.to_string(), Some(value), false);
+                Ok(read_integer(&cache, cluster, key))
+            },
+        );
+
+        let handle = cache.clone();
+        engine.register_fn(
+            "decr",
+            move |cluster: &str, key: &str, value: i64| -> Result<Dynamic, Box<EvalAltResult>> {
+                let value = to_i32(value)?;
+                let mut cache = handle.lock().unwrap();
+                cache.decr(cluster.to_string(), key.to_string(), Some(value), false);
+                Ok(read_integer(&cache, cluster, key))
+            },
+        );
+
+        let handle = cache.clone();
+        engine.register_fn("delete", move |cluster: &str, key: &str| -> bool {
+            let cache = handle.lock().unwrap();
+            let existed = cache.exists(&cluster.to_string(), &key.to_string());
+            cache.delete(cluster, key, false);
+            existed
+        });
+
+        let handle = cache.clone();
+        engine.register_fn("exists", move |cluster: &str, key: &str| -> bool {
+            handle
+                .lock()
+                .unwrap()
+                .exists(&cluster.to_string(), &key.to_string())
+        });
+
+        let handle = cache;
+        engine.register_fn(
+            "expire",
+            move |cluster: &str, key: &str, ttl: i64| -> bool {
+                handle.lock().unwrap().expire_key(
+                    &cluster.to_string(),
+                    &key.to_string(),
+                    &(ttl.max(0) as u64),
+                    false,
+                )
+            },
+        );
+
+        engine
+    }
+}
+
+fn to_i32(value: i64) -> Result<i32, Box<EvalAltResult>> {
+    i32::try_from(value).map_err(|_| format!("{} does not fit in a 32-bit integer", value).into())
+}
+
+fn read_integer(cache: &Cache, cluster: &str, key: &str) -> Dynamic {
+    cache
+        .get(cluster, key)
+        .value
+        .and_then(vec_to_i32)
+        .map(|number| Dynamic::from(number as i64))
+        .unwrap_or(Dynamic::UNIT)
+}
+
+pub fn script_sha(source: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(source.as_bytes());
+    hex::encode(hasher.finalize())
+}
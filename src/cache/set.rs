@@ -1,11 +1,17 @@
 use std::{
-    collections::HashMap,
+    collections::BTreeMap,
     time::{Duration, Instant},
 };
 
-use crate::{logger::logger_manager::Logger, persistent::persistent_Manager};
+use crate::{
+    logger::logger_manager::Logger,
+    persistent::record::{self, PersistentRecord},
+};
 
-use super::{cache::CacheType, Cache};
+use super::{
+    cache::{is_live, CacheType, Store},
+    Cache,
+};
 
 pub trait Set {
     fn set(
@@ -27,14 +33,19 @@ impl Set for Cache {
         ttl: Option<Duration>,
         ignore_persistent: bool,
     ) -> bool {
-        let memory_usage = std::mem::size_of_val(&value);
-
+        let cluster = self.resolve_cluster(&cluster);
+        let ttl = ttl.or_else(|| self.cluster_default_ttl(&cluster));
         // Check if the memory limit is reached
         {
-            let memory_handler = self.memory_handler.lock().unwrap();
-            if memory_handler.is_memory_limit_finished() {
+            // the guard is dropped before evicting, eviction locks the memory handler itself
+            let limit_reached = self
+                .memory_handler
+                .lock()
+                .unwrap()
+                .is_memory_limit_finished();
+            if limit_reached {
                 println!("Memory limit exceeded. Evicting entries...");
-                self.evict_entries();
+                self.evict_entries(&cluster, ignore_persistent);
                 if self.enable_log {
                     let memory_handler_log =
                         Logger::log_warn("Memory limit exceeded. Evicting entries");
@@ -50,24 +61,7 @@ impl Set for Cache {
             .is_memory_limit_finished()
         {
             let mut store = self.store.lock().unwrap();
-            let cluster_store = store.entry(cluster.clone()).or_insert_with(HashMap::new);
-            let expiration_time = ttl.map(|duration| Instant::now() + duration);
-            cluster_store.insert(
-                key.clone(),
-                (value.clone(), expiration_time, ttl, CacheType::Str),
-            );
-            let mut memory_handler = self.memory_handler.lock().unwrap();
-            memory_handler.add_memory(memory_usage);
-
-            if self.enable_log {
-                let set_log = Logger::log_info("Set value in cluster");
-                set_log.write_log_to_file();
-                if self.persistent && !ignore_persistent && ttl.is_none() {
-                    let command = format!("SET {} {} {:?}", cluster, key, value);
-                    persistent_Manager::write_to_persistent_file(&command);
-                }
-            }
-            return true;
+            return self.set_in_store(&mut store, &cluster, &key, value, ttl, ignore_persistent);
         } else {
             println!("Failed to set value: Memory usage has exceeded the configured limit. Update your configuration JSON file.");
             if self.enable_log {
@@ -78,3 +72,97 @@ impl Set for Cache {
         }
     }
 }
+
+impl Cache {
+    // writes with exactly the given ttl, without one the key does not expire instead of taking
+    // the default ttl of its cluster. used where the ttl was already decided, like a replay or an import
+    pub fn set_exact(
+        &mut self,
+        cluster: &str,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+        ignore_persistent: bool,
+    ) -> bool {
+        let cluster = &self.resolve_cluster(cluster);
+        if !self.has_memory_for_write(cluster, ignore_persistent) {
+            return false;
+        }
+        let mut store = self.store.lock().unwrap();
+        self.set_in_store(&mut store, cluster, key, value, ttl, ignore_persistent)
+    }
+
+    // writes a string entry into an already locked store, so callers can check a condition
+    // and write under the same lock
+    pub fn set_in_store(
+        &self,
+        store: &mut Store,
+        cluster: &str,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+        ignore_persistent: bool,
+    ) -> bool {
+        let memory_usage = std::mem::size_of_val(&value);
+        if let Err(error) = self.check_cluster_write(store, cluster, Some(key), ignore_persistent) {
+            if self.enable_log {
+                Logger::log_warn(&format!("Failed to set value: {}", error.as_str()))
+                    .write_log_to_file();
+            }
+            return false;
+        }
+        let cluster_store = store
+            .entry(cluster.to_string())
+            .or_insert_with(BTreeMap::new);
+        let expiration_time = ttl.map(|duration| Instant::now() + duration);
+        let version = self.next_version();
+        // overwriting a live key keeps its creation time and access history, tags are set again by the caller
+        if let Some(previous) = cluster_store.get(key) {
+            self.unindex_entry_tags(cluster, key, previous);
+        }
+        let mut meta = cluster_store
+            .get(key)
+            .filter(|entry| is_live(entry, Instant::now()))
+            .map(|entry| entry.5.clone())
+            .unwrap_or_default();
+        meta.tags.clear();
+        meta.touch_write();
+        cluster_store.insert(
+            key.to_string(),
+            (
+                value.clone(),
+                expiration_time,
+                ttl,
+                CacheType::Str,
+                version,
+                meta,
+            ),
+        );
+        let mut memory_handler = self.memory_handler.lock().unwrap();
+        memory_handler.add_memory(memory_usage);
+
+        if self.enable_log {
+            let set_log = Logger::log_info("Set value in cluster");
+            set_log.write_log_to_file();
+        }
+        self.persist(set_record(cluster, key, &value, ttl), ignore_persistent);
+        true
+    }
+}
+
+// a SET carries the deadline of its ttl when it has one, replaying it after the deadline leaves the key absent
+pub fn set_record(
+    cluster: &str,
+    key: &str,
+    value: &[u8],
+    ttl: Option<Duration>,
+) -> PersistentRecord {
+    let record = PersistentRecord::new("SET")
+        .arg(cluster)
+        .arg(key)
+        .arg(value);
+    match ttl {
+        Some(ttl) => record.arg(record::deadline_millis(ttl).to_string()),
+        None => record,
+    }
+}
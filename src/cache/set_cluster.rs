@@ -1,17 +1,24 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
-use crate::logger::logger_manager::Logger;
+use crate::{logger::logger_manager::Logger, persistent::record::PersistentRecord};
 
 use super::Cache;
 
 pub trait SetCluster {
-    fn set_cluster(&self, cluster: String);
+    fn set_cluster(&self, cluster: String, ignore_persistent: bool);
 }
 
 impl SetCluster for Cache {
-    fn set_cluster(&self, cluster: String) {
+    fn set_cluster(&self, cluster: String, ignore_persistent: bool) {
+        let cluster = self.resolve_cluster(&cluster);
         let mut store = self.store.lock().unwrap();
-        store.entry(cluster).or_insert_with(HashMap::new);
+        if !store.contains_key(&cluster) {
+            store.insert(cluster.clone(), BTreeMap::new());
+            self.persist(
+                PersistentRecord::new("SET_CLUSTER").arg(&cluster),
+                ignore_persistent,
+            );
+        }
         if self.enable_log == true {
             let set_cluster_log = Logger::log_info("cluster set ");
             set_cluster_log.write_log_to_file();
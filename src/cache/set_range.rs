@@ -0,0 +1,100 @@
+use std::time::Instant;
+
+use crate::{logger::logger_manager::Logger, persistent::record::PersistentRecord};
+
+use super::{
+    cache::{CacheError, CacheType, EntryMeta},
+    string_value::live_string_entry,
+    Cache,
+};
+
+// like redis, a range write may not grow a string past 512MB
+pub const MAX_STRING_LENGTH: usize = 512 * 1024 * 1024;
+
+pub trait SetRange {
+    fn set_range(
+        &mut self,
+        cluster: &str,
+        key: &str,
+        offset: usize,
+        value: &[u8],
+        ignore_persistent: bool,
+    ) -> Result<usize, CacheError>;
+}
+
+impl SetRange for Cache {
+    fn set_range(
+        &mut self,
+        cluster: &str,
+        key: &str,
+        offset: usize,
+        value: &[u8],
+        ignore_persistent: bool,
+    ) -> Result<usize, CacheError> {
+        let cluster = &self.resolve_cluster(cluster);
+        // checked before the lock is taken so a huge offset never gets to allocate
+        offset
+            .checked_add(value.len())
+            .filter(|end| *end <= MAX_STRING_LENGTH)
+            .ok_or(CacheError::OffsetOutOfRange)?;
+        if !self.has_memory_for_write(cluster, ignore_persistent) {
+            return Err(CacheError::MemoryLimitExceeded);
+        }
+
+        let next_version = self.next_version();
+        let mut store = self.store.lock().unwrap();
+        self.check_cluster_write(&mut store, cluster, Some(key), ignore_persistent)?;
+        let new_len = match live_string_entry(store.get_mut(cluster), key)? {
+            Some((existing_value, _, _, _, version, meta)) => {
+                overwrite_at(existing_value, offset, value);
+                *version = next_version;
+                meta.touch_write();
+                existing_value.len()
+            }
+            None => {
+                let ttl = self.cluster_default_ttl(cluster);
+                // a missing key is treated as an empty string, padded with zero bytes up to the offset
+                let mut new_value = Vec::new();
+                overwrite_at(&mut new_value, offset, value);
+                let new_len = new_value.len();
+                self.memory_handler
+                    .lock()
+                    .unwrap()
+                    .add_memory(std::mem::size_of_val(&new_value));
+                store.entry(cluster.to_string()).or_default().insert(
+                    key.to_string(),
+                    (
+                        new_value,
+                        ttl.map(|duration| Instant::now() + duration),
+                        ttl,
+                        CacheType::Str,
+                        next_version,
+                        EntryMeta::new(),
+                    ),
+                );
+                new_len
+            }
+        };
+
+        if self.enable_log {
+            Logger::log_info("value range set").write_log_to_file();
+        }
+        self.persist(
+            PersistentRecord::new("SETRANGE")
+                .arg(cluster)
+                .arg(key)
+                .arg(offset.to_string())
+                .arg(value),
+            ignore_persistent,
+        );
+        Ok(new_len)
+    }
+}
+
+fn overwrite_at(target: &mut Vec<u8>, offset: usize, value: &[u8]) {
+    let end = offset + value.len();
+    if target.len() < end {
+        target.resize(end, 0);
+    }
+    target[offset..end].copy_from_slice(value);
+}
@@ -0,0 +1,165 @@
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::persistent::{
+    persistent_Manager,
+    snapshot::{SnapshotCluster, SnapshotEntry, SnapshotState},
+};
+
+use super::{
+    cache::{CacheType, EntryMeta},
+    cluster_config::ClusterConfig,
+    drop_cluster::DropCluster,
+    Cache,
+};
+
+pub trait Snapshot {
+    fn capture_snapshot(&self) -> SnapshotState;
+    fn restore_snapshot(&mut self, state: SnapshotState);
+}
+
+impl Snapshot for Cache {
+    // copies the whole state while the caller holds the cache, encoding and writing happen later without it
+    fn capture_snapshot(&self) -> SnapshotState {
+        let now = Instant::now();
+        let now_unix = unix_millis(SystemTime::now());
+        let to_unix = |deadline: Instant| {
+            now_unix + deadline.saturating_duration_since(now).as_millis() as u64
+        };
+
+        let clusters = self
+            .store
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, cluster_store)| SnapshotCluster {
+                name: name.clone(),
+                entries: cluster_store
+                    .iter()
+                    .filter(|(_, entry)| entry.1.map_or(true, |exp| exp > now))
+                    .map(
+                        |(key, (value, expiration_time, ttl, cache_type, version, meta))| {
+                            SnapshotEntry {
+                                key: key.clone(),
+                                value: value.clone(),
+                                value_type: cache_type.clone() as u8,
+                                expires_at: expiration_time.map(to_unix),
+                                ttl: ttl.map(|ttl| ttl.as_millis() as u64),
+                                version: *version,
+                                created_at: unix_millis(meta.created_at),
+                                updated_at: unix_millis(meta.updated_at),
+                                last_access: unix_millis(meta.last_access),
+                                access_count: meta.access_count,
+                                tags: meta.tags.clone(),
+                            }
+                        },
+                    )
+                    .collect(),
+            })
+            .collect();
+
+        SnapshotState {
+            created_at: now_unix,
+            log_position: persistent_Manager::current_log_position(),
+            version_seq: self.version_seq.load(Ordering::SeqCst),
+            clusters,
+            aliases: self
+                .aliases
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(alias, target)| (alias.clone(), target.clone()))
+                .collect(),
+            cluster_configs: self
+                .cluster_configs
+                .lock()
+                .unwrap()
+                .iter()
+                .filter_map(|(cluster, config)| {
+                    Some((cluster.clone(), serde_json::to_string(config).ok()?))
+                })
+                .collect(),
+            cluster_expirations: self
+                .cluster_expirations
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(cluster, deadline)| (cluster.clone(), to_unix(*deadline)))
+                .collect(),
+        }
+    }
+
+    fn restore_snapshot(&mut self, state: SnapshotState) {
+        let now = Instant::now();
+        let now_unix = unix_millis(SystemTime::now());
+        let to_instant =
+            |deadline: u64| now + Duration::from_millis(deadline.saturating_sub(now_unix));
+
+        {
+            let mut store = self.store.lock().unwrap();
+            let mut memory_handler = self.memory_handler.lock().unwrap();
+            for cluster in state.clusters {
+                let cluster_store = store.entry(cluster.name.clone()).or_default();
+                for entry in cluster.entries {
+                    if entry.expires_at.map_or(false, |exp| exp <= now_unix) {
+                        continue;
+                    }
+                    let cache_type = match entry.value_type {
+                        2 => CacheType::Int,
+                        _ => CacheType::Str,
+                    };
+                    let meta = EntryMeta {
+                        created_at: from_unix_millis(entry.created_at),
+                        updated_at: from_unix_millis(entry.updated_at),
+                        last_access: from_unix_millis(entry.last_access),
+                        access_count: entry.access_count,
+                        tags: entry.tags,
+                    };
+                    let restored = (
+                        entry.value,
+                        entry.expires_at.map(to_instant),
+                        entry.ttl.map(Duration::from_millis),
+                        cache_type,
+                        entry.version,
+                        meta,
+                    );
+                    memory_handler.add_memory(std::mem::size_of_val(&restored.0));
+                    self.index_entry_tags(&cluster.name, &entry.key, &restored);
+                    cluster_store.insert(entry.key, restored);
+                }
+            }
+        }
+
+        self.version_seq
+            .fetch_max(state.version_seq, Ordering::SeqCst);
+        self.aliases.lock().unwrap().extend(state.aliases);
+        {
+            let mut cluster_configs = self.cluster_configs.lock().unwrap();
+            for (cluster, config) in state.cluster_configs {
+                if let Ok(config) = serde_json::from_str::<ClusterConfig>(&config) {
+                    cluster_configs.insert(cluster, config);
+                }
+            }
+        }
+        for (cluster, deadline) in state.cluster_expirations {
+            if deadline <= now_unix {
+                self.drop_cluster(&cluster, true);
+            } else {
+                self.cluster_expirations
+                    .lock()
+                    .unwrap()
+                    .insert(cluster, to_instant(deadline));
+            }
+        }
+    }
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn from_unix_millis(millis: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis)
+}
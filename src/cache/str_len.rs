@@ -0,0 +1,14 @@
+use super::{cache::CacheError, string_value::live_string_entry, Cache};
+
+pub trait StrLen {
+    fn str_len(&self, cluster: &str, key: &str) -> Result<usize, CacheError>;
+}
+
+impl StrLen for Cache {
+    fn str_len(&self, cluster: &str, key: &str) -> Result<usize, CacheError> {
+        let cluster = &self.resolve_cluster(cluster);
+        let mut store = self.store.lock().unwrap();
+        let entry = live_string_entry(store.get_mut(cluster), key)?;
+        Ok(entry.map_or(0, |(value, _, _, _, _, _)| value.len()))
+    }
+}
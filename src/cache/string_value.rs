@@ -0,0 +1,35 @@
+use std::time::Instant;
+
+use super::cache::{CacheEntry, CacheError, CacheType, ClusterStore};
+
+// live string entry of a key; expired entries count as missing and int entries are a type error
+pub fn live_string_entry<'a>(
+    cluster_store: Option<&'a mut ClusterStore>,
+    key: &str,
+) -> Result<Option<&'a mut CacheEntry>, CacheError> {
+    let entry = cluster_store.and_then(|cluster_store| cluster_store.get_mut(key));
+    match entry {
+        Some(entry) if entry.1.map_or(false, |exp| exp <= Instant::now()) => Ok(None),
+        Some(entry) => match entry.3 {
+            CacheType::Str => Ok(Some(entry)),
+            CacheType::Int => Err(CacheError::WrongType),
+        },
+        None => Ok(None),
+    }
+}
+
+// redis style inclusive range where negative indexes count from the end
+pub fn resolve_range(len: usize, start: i64, end: i64) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let end = if end < 0 { len + end } else { end.min(len - 1) };
+    if len == 0 || start > end || start >= len {
+        None
+    } else {
+        Some((start as usize, end as usize))
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::{
+    logger::logger_manager::Logger,
+    persistent::record::{self, PersistentRecord},
+};
+
+use super::{
+    cache::{is_live, CacheEntry, CacheError, ClusterStore},
+    Cache,
+};
+
+// tag -> (cluster, key) of every entry carrying it, the entries themselves keep their tags in EntryMeta
+pub type TagIndex = HashMap<String, BTreeSet<(String, String)>>;
+
+#[derive(Clone, Serialize, Debug)]
+pub struct TaggedKey {
+    pub cluster: String,
+    pub key: String,
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct InvalidateResult {
+    pub tag: String,
+    pub invalidated: usize,
+}
+
+pub trait Tagging {
+    fn tag_key(
+        &mut self,
+        cluster: &str,
+        key: &str,
+        tags: &[String],
+        ignore_persistent: bool,
+    ) -> Result<(), CacheError>;
+    fn tagged_keys(&self, tag: &str) -> Vec<TaggedKey>;
+    fn invalidate_tag(
+        &mut self,
+        tag: &str,
+        ttl: Option<Duration>,
+        ignore_persistent: bool,
+    ) -> InvalidateResult;
+}
+
+impl Tagging for Cache {
+    // the tags are all added or, when one of them is not valid, none of them
+    fn tag_key(
+        &mut self,
+        cluster: &str,
+        key: &str,
+        tags: &[String],
+        ignore_persistent: bool,
+    ) -> Result<(), CacheError> {
+        if !tags.iter().all(|tag| valid_tag(tag)) {
+            return Err(CacheError::InvalidTag);
+        }
+        let cluster = &self.resolve_cluster(cluster);
+        let mut store = self.store.lock().unwrap();
+        let entry = match store
+            .get_mut(cluster)
+            .and_then(|cluster_store| cluster_store.get_mut(key))
+            .filter(|entry| is_live(entry, Instant::now()))
+        {
+            Some(entry) => entry,
+            None => return Err(CacheError::KeyNotFound),
+        };
+
+        let mut tag_index = self.tag_index.lock().unwrap();
+        for tag in tags.iter() {
+            if !entry.5.tags.contains(tag) {
+                entry.5.tags.push(tag.clone());
+            }
+            tag_index
+                .entry(tag.clone())
+                .or_default()
+                .insert((cluster.to_string(), key.to_string()));
+        }
+
+        self.persist(
+            entry.5.tags.iter().fold(
+                PersistentRecord::new("TAG").arg(cluster).arg(key),
+                |record, tag| record.arg(tag),
+            ),
+            ignore_persistent,
+        );
+        Ok(())
+    }
+
+    fn tagged_keys(&self, tag: &str) -> Vec<TaggedKey> {
+        self.tag_index
+            .lock()
+            .unwrap()
+            .get(tag)
+            .map(|keys| {
+                keys.iter()
+                    .map(|(cluster, key)| TaggedKey {
+                        cluster: cluster.clone(),
+                        key: key.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // deletes every key carrying the tag, or only gives them a ttl when one is passed
+    fn invalidate_tag(
+        &mut self,
+        tag: &str,
+        ttl: Option<Duration>,
+        ignore_persistent: bool,
+    ) -> InvalidateResult {
+        let mut store = self.store.lock().unwrap();
+        let now = Instant::now();
+        let mut invalidated = 0;
+        for TaggedKey { cluster, key } in self.tagged_keys(tag) {
+            let live = store
+                .get(&cluster)
+                .and_then(|cluster_store| cluster_store.get(&key))
+                .map_or(false, |entry| is_live(entry, now));
+            if !live
+                || self
+                    .check_cluster_write(&mut store, &cluster, None, ignore_persistent)
+                    .is_err()
+            {
+                continue;
+            }
+            let cluster_store = store.get_mut(&cluster).unwrap();
+            match ttl {
+                Some(ttl) => {
+                    let version = self.next_version();
+                    let entry = cluster_store.get_mut(&key).unwrap();
+                    entry.1 = Some(now + ttl);
+                    entry.2 = Some(ttl);
+                    entry.4 = version;
+                    entry.5.touch_write();
+                }
+                None => {
+                    let entry = cluster_store.remove(&key).unwrap();
+                    self.unindex_entry_tags(&cluster, &key, &entry);
+                    self.memory_handler
+                        .lock()
+                        .unwrap()
+                        .delete_memory(std::mem::size_of_val(&entry.0));
+                }
+            }
+            invalidated += 1;
+        }
+
+        Logger::log_info_data(&format!("tag: {} invalidated {} keys", tag, invalidated))
+            .write_log_to_file();
+        let record = match ttl {
+            Some(ttl) => PersistentRecord::new("INVALIDATE_TAG")
+                .arg(tag)
+                .arg(record::deadline_millis(ttl).to_string()),
+            None => PersistentRecord::new("INVALIDATE_TAG").arg(tag),
+        };
+        self.persist(record, ignore_persistent);
+        InvalidateResult {
+            tag: tag.to_string(),
+            invalidated,
+        }
+    }
+}
+
+impl Cache {
+    // registers the tags an entry carries, used when an entry lands under a new name
+    pub fn index_entry_tags(&self, cluster: &str, key: &str, entry: &CacheEntry) {
+        if entry.5.tags.is_empty() {
+            return;
+        }
+        let mut tag_index = self.tag_index.lock().unwrap();
+        for tag in entry.5.tags.iter() {
+            tag_index
+                .entry(tag.clone())
+                .or_default()
+                .insert((cluster.to_string(), key.to_string()));
+        }
+    }
+
+    // must be called whenever an entry leaves the store so the index never points at missing keys
+    pub fn unindex_entry_tags(&self, cluster: &str, key: &str, entry: &CacheEntry) {
+        if entry.5.tags.is_empty() {
+            return;
+        }
+        let mut tag_index = self.tag_index.lock().unwrap();
+        let target = (cluster.to_string(), key.to_string());
+        for tag in entry.5.tags.iter() {
+            if let Some(keys) = tag_index.get_mut(tag) {
+                keys.remove(&target);
+                if keys.is_empty() {
+                    tag_index.remove(tag);
+                }
+            }
+        }
+    }
+
+    pub fn unindex_cluster_tags(&self, cluster: &str, cluster_store: &ClusterStore) {
+        for (key, entry) in cluster_store.iter() {
+            self.unindex_entry_tags(cluster, key, entry);
+        }
+    }
+}
+
+// records are length prefixed, so a tag may hold spaces or anything else as long as it is not empty
+pub fn valid_tag(tag: &str) -> bool {
+    !tag.is_empty()
+}
@@ -0,0 +1,332 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{logger::logger_manager::Logger, persistent::record::PersistentRecord};
+
+use super::{
+    cache::CacheEntry,
+    conditional_set::ConditionalSet,
+    decr::Decr,
+    delete::Delete,
+    expire_key::{expire_record, ExpireKey},
+    incr::{set_int_record, Incr},
+    set::{set_record, Set},
+    Cache,
+};
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum TransactionCommand {
+    Set {
+        cluster: String,
+        key: String,
+        value: String,
+        ttl: Option<u64>, // Duration in milliseconds
+    },
+    Incr {
+        cluster: String,
+        key: String,
+        value: Option<i32>,
+    },
+    Decr {
+        cluster: String,
+        key: String,
+        value: Option<i32>,
+    },
+    Delete {
+        cluster: String,
+        key: String,
+    },
+    Expire {
+        cluster: String,
+        key: String,
+        ttl: u64,
+    },
+}
+
+impl TransactionCommand {
+    fn target(&self) -> (&String, &String) {
+        match self {
+            TransactionCommand::Set { cluster, key, .. }
+            | TransactionCommand::Incr { cluster, key, .. }
+            | TransactionCommand::Decr { cluster, key, .. }
+            | TransactionCommand::Delete { cluster, key }
+            | TransactionCommand::Expire { cluster, key, .. } => (cluster, key),
+        }
+    }
+
+    fn cluster_mut(&mut self) -> &mut String {
+        match self {
+            TransactionCommand::Set { cluster, .. }
+            | TransactionCommand::Incr { cluster, .. }
+            | TransactionCommand::Decr { cluster, .. }
+            | TransactionCommand::Delete { cluster, .. }
+            | TransactionCommand::Expire { cluster, .. } => cluster,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct WatchedKey {
+    pub cluster: String,
+    pub key: String,
+    pub version: Option<u64>, // None when the key did not exist at watch time
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub enum TransactionStatus {
+    Committed,
+    WatchFailed,
+    CommandFailed,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TransactionResult {
+    pub status: TransactionStatus,
+    pub executed_commands: usize,
+    pub failed_command: Option<usize>,
+}
+
+pub trait Transaction {
+    fn watch(&self, keys: &[(String, String)]) -> Vec<WatchedKey>;
+    fn exec(
+        &mut self,
+        watched: &[WatchedKey],
+        commands: &[TransactionCommand],
+    ) -> TransactionResult;
+}
+
+impl Transaction for Cache {
+    fn watch(&self, keys: &[(String, String)]) -> Vec<WatchedKey> {
+        keys.iter()
+            .map(|(cluster, key)| WatchedKey {
+                cluster: cluster.clone(),
+                key: key.clone(),
+                version: self.key_version(cluster, key),
+            })
+            .collect()
+    }
+
+    fn exec(
+        &mut self,
+        watched: &[WatchedKey],
+        commands: &[TransactionCommand],
+    ) -> TransactionResult {
+        // optimistic lock: abort when any watched key changed since it was read
+        let changed = watched
+            .iter()
+            .any(|watch| self.key_version(&watch.cluster, &watch.key) != watch.version);
+        if changed {
+            if self.enable_log {
+                Logger::log_warn("transaction aborted: watched key changed").write_log_to_file();
+            }
+            return TransactionResult {
+                status: TransactionStatus::WatchFailed,
+                executed_commands: 0,
+                failed_command: None,
+            };
+        }
+
+        // aliases are resolved once so the snapshot and the commands address the same clusters
+        let commands: Vec<TransactionCommand> = commands
+            .iter()
+            .cloned()
+            .map(|mut command| {
+                let cluster = command.cluster_mut();
+                *cluster = self.resolve_cluster(cluster);
+                command
+            })
+            .collect();
+        let commands = commands.as_slice();
+
+        // keep the original state of every touched key so a failing command rolls back the whole transaction
+        let mut snapshot: Vec<(String, String, bool, Option<CacheEntry>)> = Vec::new();
+        {
+            let store = self.store.lock().unwrap();
+            for command in commands {
+                let (cluster, key) = command.target();
+                if snapshot.iter().any(|(c, k, _, _)| c == cluster && k == key) {
+                    continue;
+                }
+                let cluster_store = store.get(cluster);
+                snapshot.push((
+                    cluster.clone(),
+                    key.clone(),
+                    cluster_store.is_some(),
+                    cluster_store.and_then(|cluster_store| cluster_store.get(key).cloned()),
+                ));
+            }
+        }
+        let memory_before = self.memory_handler.lock().unwrap().current_memory();
+
+        let mut persistent_commands: Vec<PersistentRecord> = Vec::new();
+        for (index, command) in commands.iter().enumerate() {
+            let applied = self.apply_transaction_command(command, &mut persistent_commands);
+            if !applied {
+                self.rollback_transaction(snapshot, memory_before);
+                if self.enable_log {
+                    Logger::log_error_data(&format!(
+                        "transaction rolled back: command {} failed",
+                        index
+                    ))
+                    .write_log_to_file();
+                }
+                return TransactionResult {
+                    status: TransactionStatus::CommandFailed,
+                    executed_commands: index,
+                    failed_command: Some(index),
+                };
+            }
+        }
+
+        for command in persistent_commands {
+            self.persist(command, false);
+        }
+        if self.enable_log {
+            Logger::log_info("transaction committed").write_log_to_file();
+        }
+        TransactionResult {
+            status: TransactionStatus::Committed,
+            executed_commands: commands.len(),
+            failed_command: None,
+        }
+    }
+}
+
+impl Cache {
+    // runs one queued command without persisting it; the persistent line is only written once the whole transaction commits
+    fn apply_transaction_command(
+        &mut self,
+        command: &TransactionCommand,
+        persistent_commands: &mut Vec<PersistentRecord>,
+    ) -> bool {
+        match command {
+            TransactionCommand::Set {
+                cluster,
+                key,
+                value,
+                ttl,
+            } => {
+                let value = value.as_bytes().to_vec();
+                let applied = self.set(
+                    cluster.clone(),
+                    key.clone(),
+                    value.clone(),
+                    ttl.map(Duration::from_millis),
+                    true,
+                );
+                if applied {
+                    persistent_commands.push(set_record(
+                        cluster,
+                        key,
+                        &value,
+                        ttl.map(Duration::from_millis)
+                            .or_else(|| self.cluster_default_ttl(cluster)),
+                    ));
+                }
+                applied
+            }
+            TransactionCommand::Incr {
+                cluster,
+                key,
+                value,
+            } => {
+                let applied = self.incr(cluster.clone(), key.clone(), *value, true);
+                if applied {
+                    let (current_value, expiration) = self.current_counter(cluster, key);
+                    persistent_commands.push(set_int_record(
+                        cluster,
+                        key,
+                        &current_value,
+                        expiration,
+                    ));
+                }
+                applied
+            }
+            TransactionCommand::Decr {
+                cluster,
+                key,
+                value,
+            } => {
+                let applied = self.decr(cluster.clone(), key.clone(), *value, true);
+                if applied {
+                    let (current_value, expiration) = self.current_counter(cluster, key);
+                    persistent_commands.push(set_int_record(
+                        cluster,
+                        key,
+                        &current_value,
+                        expiration,
+                    ));
+                }
+                applied
+            }
+            TransactionCommand::Delete { cluster, key } => {
+                if self.is_cluster_read_only(cluster) {
+                    return false;
+                }
+                self.delete(cluster, key, true);
+                persistent_commands.push(PersistentRecord::new("DEL").arg(cluster).arg(key));
+                true
+            }
+            TransactionCommand::Expire { cluster, key, ttl } => {
+                let applied = self.expire_key(cluster, key, ttl, true);
+                if applied {
+                    persistent_commands.push(expire_record(
+                        cluster,
+                        key,
+                        Duration::from_millis(*ttl),
+                    ));
+                }
+                applied
+            }
+        }
+    }
+
+    fn current_counter(&self, cluster: &str, key: &str) -> (Vec<u8>, Option<Instant>) {
+        let store = self.store.lock().unwrap();
+        store
+            .get(cluster)
+            .and_then(|cluster_store| cluster_store.get(key))
+            .map(|(value, expiration, _, _, _, _)| (value.clone(), *expiration))
+            .unwrap_or_default()
+    }
+
+    fn rollback_transaction(
+        &self,
+        snapshot: Vec<(String, String, bool, Option<CacheEntry>)>,
+        memory_before: usize,
+    ) {
+        let mut store = self.store.lock().unwrap();
+        for (cluster, key, cluster_existed, entry) in snapshot {
+            match entry {
+                Some(entry) => {
+                    let cluster_store = store.entry(cluster.clone()).or_default();
+                    if let Some(current) = cluster_store.get(&key) {
+                        self.unindex_entry_tags(&cluster, &key, current);
+                    }
+                    self.index_entry_tags(&cluster, &key, &entry);
+                    cluster_store.insert(key, entry);
+                }
+                None => {
+                    if let Some(cluster_store) = store.get_mut(&cluster) {
+                        if let Some(entry) = cluster_store.remove(&key) {
+                            self.unindex_entry_tags(&cluster, &key, &entry);
+                        }
+                        if !cluster_existed && cluster_store.is_empty() {
+                            store.remove(&cluster);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut memory_handler = self.memory_handler.lock().unwrap();
+        let memory_after = memory_handler.current_memory();
+        if memory_after > memory_before {
+            memory_handler.delete_memory(memory_after - memory_before);
+        } else {
+            memory_handler.add_memory(memory_before - memory_after);
+        }
+    }
+}
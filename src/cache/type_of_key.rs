@@ -6,12 +6,13 @@ pub trait TypeOfKey {
 
 impl TypeOfKey for Cache {
     fn get_type(&self, cluster: &String, key: &String) -> Option<CacheType> {
+        let cluster = &self.resolve_cluster(cluster);
         let store = self.store.lock().unwrap();
         let cahe_type = store.get(cluster).and_then(|cluster_store| {
             cluster_store
                 .get(key)
                 .cloned()
-                .map(|(_, _, _, cache_type)| cache_type)
+                .map(|(_, _, _, cache_type, _, _)| cache_type)
         });
         let mut value_type = None;
         if ((cahe_type.as_ref().is_some())
@@ -0,0 +1,176 @@
+use crate::build_cache;
+use crate::cache::dump::{DumpExport, DumpImporter, ImportMode};
+use crate::cache::point_in_time::{parse_point_in_time, PointInTime};
+use crate::cache::Cache;
+use crate::config::Settings;
+use crate::creds::cred_manager::CredsManager;
+use crate::known_directories::{self, DirectoryOptions};
+use crate::persistent::persistent_Manager;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+const USAGE: &str = "usage:
+  quebrix [--config <file>] [--data-dir <dir>] [--log-dir <dir>] [--creds-dir <dir>] [command]
+commands:
+  quebrix export <file> [--cluster <name>]...
+  quebrix import <file> [--mode merge|replace]
+  quebrix recover <file> --until <unix millis|RFC 3339 date> [--cluster <name>]...";
+
+// options that place the config and the directories, taken out of the arguments
+// before anything reads them and accepted with or without a subcommand
+const DIRECTORY_FLAGS: [&str; 4] = ["--config", "--data-dir", "--log-dir", "--creds-dir"];
+
+pub fn configure_directories(args: Vec<String>) -> Vec<String> {
+    let mut options = DirectoryOptions::default();
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if !DIRECTORY_FLAGS.contains(&arg.as_str()) {
+            rest.push(arg);
+            continue;
+        }
+        let Some(value) = args.next().map(PathBuf::from) else {
+            eprintln!("{} needs a value\n{}", arg, USAGE);
+            std::process::exit(1);
+        };
+        match arg.as_str() {
+            "--config" => options.config_file = Some(value),
+            "--data-dir" => options.data_directory = Some(value),
+            "--log-dir" => options.log_directory = Some(value),
+            _ => options.creds_directory = Some(value),
+        }
+    }
+    known_directories::configure_directories(options);
+    rest
+}
+
+// runs a subcommand given on the command line, returns false when there is none and the server should start.
+// the subcommands work on the persisted data directly, so they are meant for a stopped instance
+pub fn run(args: &[String]) -> bool {
+    let command = match args.first() {
+        Some(command) => command.as_str(),
+        None => return false,
+    };
+    let result = match command {
+        "export" => export(&args[1..]),
+        "import" => import(&args[1..]),
+        "recover" => recover(&args[1..]),
+        "help" | "--help" | "-h" => Ok(USAGE.to_string()),
+        _ => Err(format!("unknown command {}\n{}", command, USAGE)),
+    };
+    match result {
+        Ok(message) => println!("{}", message),
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    }
+    true
+}
+
+fn export(args: &[String]) -> Result<String, String> {
+    let path = args.first().ok_or(USAGE)?;
+    let clusters = option_values(args, "--cluster")?;
+
+    let settings = Settings::new();
+    let cache = open_cache(&settings);
+    let mut export = DumpExport::new(&cache, &clusters)?;
+    let lines = write_dump(&cache, &mut export, path)?;
+    Ok(format!("exported {} keys to {}", lines, path))
+}
+
+fn import(args: &[String]) -> Result<String, String> {
+    let path = args.first().ok_or(USAGE)?;
+    let mode = match option_value(args, "--mode")? {
+        Some(mode) => mode.parse::<ImportMode>()?,
+        None => ImportMode::default(),
+    };
+
+    let settings = Settings::new();
+    if !settings.persistent {
+        return Err("persistence is disabled, an offline import would be lost".to_string());
+    }
+    let mut cache = open_cache(&settings);
+    let reader = BufReader::new(File::open(path).map_err(|error| error.to_string())?);
+    let mut importer = DumpImporter::new(mode);
+    for line in reader.lines() {
+        let line = line.map_err(|error| error.to_string())?;
+        importer.import_line(&mut cache, &line);
+    }
+    persistent_Manager::flush_persistent_file(true).map_err(|error| error.to_string())?;
+
+    let summary = &importer.summary;
+    let mut message = format!(
+        "imported {} keys, skipped {}",
+        summary.imported, summary.skipped
+    );
+    if !summary.replaced_clusters.is_empty() {
+        message.push_str(&format!(
+            ", replaced clusters: {}",
+            summary.replaced_clusters.join(", ")
+        ));
+    }
+    for error in summary.errors.iter() {
+        message.push_str(&format!("\n  {}", error));
+    }
+    Ok(message)
+}
+
+// writes the clusters as they were at a point in time to a dump, which `import` can load later
+fn recover(args: &[String]) -> Result<String, String> {
+    let path = args.first().ok_or(USAGE)?;
+    let until = option_value(args, "--until")?.ok_or("--until is required")?;
+    let until = parse_point_in_time(&until)?;
+    let clusters = option_values(args, "--cluster")?;
+
+    let settings = Settings::new();
+    // opening the cache brings every persistent file to the current format first
+    let cache = open_cache(&settings);
+    let mut recovered = cache.recovery_cache();
+    let replay = recovered.replay_until(until)?;
+    let mut export = DumpExport::new(&recovered, &clusters)
+        .map_err(|error| format!("{} at that point in time", error))?;
+    let lines = write_dump(&recovered, &mut export, path)?;
+    Ok(format!(
+        "replayed {} commands from {} files, {} written after {} left out\nexported {} keys to {}",
+        replay.applied, replay.files, replay.after_target, until, lines, path
+    ))
+}
+
+// returns how many keys were written
+fn write_dump(cache: &Cache, export: &mut DumpExport, path: &str) -> Result<usize, String> {
+    let mut writer = BufWriter::new(File::create(path).map_err(|error| error.to_string())?);
+    let mut lines = 0;
+    while let Some(chunk) = export.next_chunk(cache) {
+        lines += chunk.iter().filter(|byte| **byte == b'\n').count();
+        writer
+            .write_all(&chunk)
+            .map_err(|error| error.to_string())?;
+    }
+    writer.flush().map_err(|error| error.to_string())?;
+    Ok(lines)
+}
+
+fn open_cache(settings: &Settings) -> Cache {
+    let cred_manager = Arc::new(Mutex::new(CredsManager::new(settings.enable_logger)));
+    build_cache(settings, cred_manager)
+}
+
+fn option_value(args: &[String], name: &str) -> Result<Option<String>, String> {
+    Ok(option_values(args, name)?.pop())
+}
+
+// every value given for an option that may be repeated
+fn option_values(args: &[String], name: &str) -> Result<Vec<String>, String> {
+    args.iter()
+        .enumerate()
+        .filter(|(_, arg)| *arg == name)
+        .map(|(index, _)| {
+            args.get(index + 1)
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", name))
+        })
+        .collect()
+}
@@ -2,9 +2,9 @@ use serde::Deserialize;
 use std::env;
 use std::fs::File;
 use std::io::BufReader;
-use std::path::PathBuf;
 
-use crate::known_directories::KNOWN_DIRECTORIES;
+use crate::known_directories::{CONFIG_FILE, CONFIG_FILE_ENV};
+use crate::persistent::persistent_Manager::AppendFsync;
 
 #[derive(Debug, Deserialize)]
 pub struct Settings {
@@ -14,19 +14,28 @@ pub struct Settings {
     pub enable_logger: bool,
     pub persistent: bool,
     pub retention_policy: i64,
+    pub script_max_operations: Option<u64>,
+    pub script_timeout: Option<u64>,       // Duration in milliseconds
+    pub snapshot_interval: Option<u64>,    // Duration in seconds, 0 disables scheduled snapshots
+    pub log_rewrite_min_size: Option<u64>, // Size in megabytes
+    pub appendfsync: Option<AppendFsync>,  // always, everysec or no
+    pub log_rewrite_growth: Option<u64>, // Growth in percent since the last rewrite, 0 disables it
+    pub encryption_key: Option<String>, // 64 hex characters, persistence and snapshots are sealed with it
+    pub encryption_key_file: Option<String>, // file holding the key instead of the config
+    pub encryption_previous_keys: Option<Vec<String>>, // rotated out keys, still read until the next log rewrite
+    pub data_directory: Option<String>, // relative paths start at the directory of this file
+    pub log_directory: Option<String>,
+    pub creds_directory: Option<String>,
 }
 
 impl Settings {
     pub fn new() -> Self {
-        let current_dir = &KNOWN_DIRECTORIES.app_root_directory;
-
-        let config_path = PathBuf::from(current_dir)
-            .join("config")
-            .join("config.json");
+        let config_path = &*CONFIG_FILE;
         let file = File::open(&config_path).expect(
             format!(
-                "config.json file not found in {}",
-                config_path.as_os_str().to_str().unwrap()
+                "config.json file not found in {}, pass --config or set {}",
+                config_path.as_os_str().to_str().unwrap(),
+                CONFIG_FILE_ENV
             )
             .as_str(),
         );
@@ -1,7 +1,10 @@
 use aes::Aes256;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
 use block_modes::{BlockMode, Cbc};
 use block_modes::block_padding::Pkcs7;
 use rand::Rng;
+use sha1::{Digest, Sha1};
 
 type Aes256Cbc = Cbc<Aes256, Pkcs7>;
 
@@ -55,3 +58,96 @@ impl Encryptor {
 
 }
 
+pub const KEY_ID_LEN: usize = 8;
+const NONCE_LEN: usize = 12;
+
+// an AES-256-GCM key for data at rest, known by an id derived from the key itself
+pub struct SealingKey {
+    id: [u8; KEY_ID_LEN],
+    cipher: Aes256Gcm,
+}
+
+impl SealingKey {
+    // keys are 32 bytes written as 64 hex characters
+    pub fn from_hex(hex_key: &str) -> Result<Self, String> {
+        let key = hex::decode(hex_key.trim())
+            .ok()
+            .filter(|key| key.len() == 32)
+            .ok_or("an encryption key must be 64 hex characters")?;
+        let mut id = [0u8; KEY_ID_LEN];
+        id.copy_from_slice(&Sha1::digest(&key)[..KEY_ID_LEN]);
+        Ok(SealingKey {
+            id,
+            cipher: Aes256Gcm::new_from_slice(&key).map_err(|error| error.to_string())?,
+        })
+    }
+}
+
+pub enum OpenError {
+    // sealed with a key that is not in the keyring, holds the key id
+    UnknownKey(String),
+    // the data was changed or cut, authentication failed
+    Rejected,
+}
+
+// seals with the current key and opens with the current or any previous one,
+// so data written before a key rotation stays readable until it is rewritten
+pub struct Keyring {
+    current: Option<SealingKey>,
+    previous: Vec<SealingKey>,
+}
+
+impl Keyring {
+    pub fn new(current: Option<SealingKey>, previous: Vec<SealingKey>) -> Self {
+        Keyring { current, previous }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.current.is_some()
+    }
+
+    // the key id, a random nonce and the ciphertext with its tag, None when there is no current key
+    pub fn seal(&self, plaintext: &[u8]) -> Option<Vec<u8>> {
+        let key = self.current.as_ref()?;
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill(&mut nonce);
+        let ciphertext = key
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad: &key.id,
+                },
+            )
+            .ok()?;
+        let mut sealed = Vec::with_capacity(KEY_ID_LEN + NONCE_LEN + ciphertext.len());
+        sealed.extend_from_slice(&key.id);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        Some(sealed)
+    }
+
+    pub fn open(&self, sealed: &[u8]) -> Result<Vec<u8>, OpenError> {
+        if sealed.len() < KEY_ID_LEN + NONCE_LEN {
+            return Err(OpenError::Rejected);
+        }
+        let (id, rest) = sealed.split_at(KEY_ID_LEN);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        let key = self
+            .current
+            .iter()
+            .chain(self.previous.iter())
+            .find(|key| key.id == id)
+            .ok_or_else(|| OpenError::UnknownKey(hex::encode(id)))?;
+        key.cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: id,
+                },
+            )
+            .map_err(|_| OpenError::Rejected)
+    }
+}
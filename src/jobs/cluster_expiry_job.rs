@@ -0,0 +1,32 @@
+use crate::{
+    cache::{expire_cluster::ExpireCluster, Cache},
+    logger::logger_manager::Logger,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+const CLUSTER_EXPIRY_INTERVAL: Duration = Duration::from_secs(1);
+// entries looked at per tick, the sweep goes through the whole cache a batch at a time
+// so the cache lock is never held for a pass over every key
+const EXPIRY_SWEEP_BATCH: usize = 10_000;
+
+pub fn run_cluster_expiry(cache: Arc<Mutex<Cache>>) {
+    let mut sweep_cursor: Option<(String, String)> = None;
+    loop {
+        let dropped = {
+            let mut cache = cache.lock().unwrap();
+            // expired keys are swept too so their memory and tags are released without a read
+            sweep_cursor = cache.clear_expired_batch(sweep_cursor.take(), EXPIRY_SWEEP_BATCH);
+            cache.clear_expired_locks();
+            cache.clear_stale_rate_limits();
+            cache.drop_expired_clusters()
+        };
+        for cluster in dropped {
+            let message = format!("cluster {} expired and was dropped", cluster);
+            Logger::log_info_data(&message).write_log_to_file();
+        }
+        std::thread::sleep(CLUSTER_EXPIRY_INTERVAL);
+    }
+}
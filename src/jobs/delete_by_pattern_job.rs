@@ -0,0 +1,102 @@
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::{
+    cache::{delete::Delete, key_pattern::KeyPattern, scan::Scan, Cache},
+    logger::logger_manager::Logger,
+};
+
+#[derive(Clone, Serialize, Debug, PartialEq)]
+pub enum PatternJobStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct DeleteByPatternProgress {
+    pub id: u64,
+    pub cluster: String,
+    pub pattern: String,
+    pub status: PatternJobStatus,
+    pub total_keys: i64,
+    pub batches: u64,
+    pub deleted: u64,
+    pub message: Option<String>,
+}
+
+// pause between batches so regular requests get the cache lock in between
+const BATCH_PAUSE: Duration = Duration::from_millis(1);
+
+pub fn start_delete_by_pattern(
+    cache: Arc<Mutex<Cache>>,
+    cluster: String,
+    pattern: KeyPattern,
+    batch_size: usize,
+    total_keys: i64,
+) -> DeleteByPatternProgress {
+    let progress = {
+        let cache = cache.lock().unwrap();
+        let progress = DeleteByPatternProgress {
+            id: cache.job_seq.fetch_add(1, Ordering::SeqCst) + 1,
+            cluster: cluster.clone(),
+            pattern: pattern.as_str().to_string(),
+            status: PatternJobStatus::Running,
+            total_keys,
+            batches: 0,
+            deleted: 0,
+            message: None,
+        };
+        let mut jobs = cache.delete_jobs.lock().unwrap();
+        jobs.retain(|_, job| job.status == PatternJobStatus::Running);
+        jobs.insert(progress.id, progress.clone());
+        progress
+    };
+
+    let job_id = progress.id;
+    std::thread::spawn(move || {
+        let mut cursor: Option<String> = None;
+        loop {
+            let finished = {
+                let cache = cache.lock().unwrap();
+                let page = cache.scan_keys(&cluster, cursor.as_deref(), batch_size, Some(&pattern));
+                let mut jobs = cache.delete_jobs.lock().unwrap();
+                let job = jobs.get_mut(&job_id).unwrap();
+                match page {
+                    Ok(page) => {
+                        // keys a read only cluster refuses to delete are not counted
+                        let deleted = page
+                            .items
+                            .iter()
+                            .filter(|key| cache.delete(&cluster, key, false))
+                            .count();
+                        job.batches += 1;
+                        job.deleted += deleted as u64;
+                        cursor = page.cursor;
+                        if cursor.is_none() {
+                            job.status = PatternJobStatus::Completed;
+                        }
+                    }
+                    Err(message) => {
+                        // the cluster was dropped while the job was running
+                        job.status = PatternJobStatus::Failed;
+                        job.message = Some(message);
+                    }
+                }
+                job.status != PatternJobStatus::Running
+            };
+            if finished {
+                break;
+            }
+            std::thread::sleep(BATCH_PAUSE);
+        }
+
+        let message = format!("delete by pattern job {} finished", job_id);
+        Logger::log_info_data(&message).write_log_to_file();
+    });
+
+    progress
+}
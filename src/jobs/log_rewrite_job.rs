@@ -0,0 +1,75 @@
+use crate::{
+    cache::{snapshot::Snapshot, Cache},
+    logger::logger_manager::Logger,
+    persistent::{log_rewrite, persistent_Manager, persistent_Manager::LogPosition, snapshot},
+};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use super::snapshot_job::PERSISTENCE_JOB_IN_PROGRESS;
+
+const REWRITE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+// log size right after the last rewrite, growth is measured against it
+static SIZE_AFTER_LAST_REWRITE: AtomicU64 = AtomicU64::new(0);
+
+// starts a background log rewrite, returns false when a snapshot or log rewrite is already running
+pub fn start_log_rewrite(cache: Arc<Mutex<Cache>>) -> bool {
+    if PERSISTENCE_JOB_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    std::thread::spawn(move || {
+        // the cache is only held while the state is copied, writes keep going to the old log meanwhile
+        let state = cache.lock().unwrap().capture_snapshot();
+        match log_rewrite::rewrite_log(&state) {
+            Ok(result) => {
+                SIZE_AFTER_LAST_REWRITE.store(result.size_after, Ordering::SeqCst);
+                let message = format!(
+                    "log rewritten with {} commands, {} bytes => {} bytes",
+                    result.commands, result.size_before, result.size_after
+                );
+                Logger::log_info_data(&message).write_log_to_file();
+
+                // older snapshots point at offsets of the replaced log, so one matching the new log takes their place
+                let state = snapshot::SnapshotState {
+                    log_position: LogPosition {
+                        file: state.log_position.file.clone(),
+                        offset: result.rewritten_offset,
+                    },
+                    ..state
+                };
+                match snapshot::write_snapshot(&state) {
+                    Ok(path) => snapshot::remove_snapshots_except(&path),
+                    Err(error) => {
+                        let message = format!("snapshot after log rewrite failed: {}", error);
+                        Logger::log_error_data(&message).write_log_to_file();
+                    }
+                }
+            }
+            Err(error) => {
+                let message = format!("log rewrite failed: {}", error);
+                Logger::log_error_data(&message).write_log_to_file();
+            }
+        }
+        PERSISTENCE_JOB_IN_PROGRESS.store(false, Ordering::SeqCst);
+    });
+    true
+}
+
+// rewrites the log once it is at least `min_size` bytes and has grown by `growth_percentage` since the last rewrite
+pub fn run_log_rewrite_checks(cache: Arc<Mutex<Cache>>, min_size: u64, growth_percentage: u64) {
+    SIZE_AFTER_LAST_REWRITE.store(persistent_Manager::persistent_log_size(), Ordering::SeqCst);
+    loop {
+        std::thread::sleep(REWRITE_CHECK_INTERVAL);
+        let size = persistent_Manager::persistent_log_size();
+        let base = SIZE_AFTER_LAST_REWRITE.load(Ordering::SeqCst);
+        if size >= min_size && size >= base + base * growth_percentage / 100 {
+            start_log_rewrite(cache.clone());
+        }
+    }
+}
@@ -1 +1,6 @@
-pub mod retention_policy_job;
\ No newline at end of file
+pub mod cluster_expiry_job;
+pub mod delete_by_pattern_job;
+pub mod log_rewrite_job;
+pub mod persistent_flush_job;
+pub mod retention_policy_job;
+pub mod snapshot_job;
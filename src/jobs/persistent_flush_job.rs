@@ -0,0 +1,20 @@
+use crate::{
+    logger::logger_manager::Logger,
+    persistent::persistent_Manager::{self, AppendFsync},
+};
+use std::time::Duration;
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+// pushes buffered records out once a second, syncing them to disk unless the OS is left to do it
+pub fn run_persistent_flush(policy: AppendFsync) {
+    loop {
+        std::thread::sleep(FLUSH_INTERVAL);
+        if let Err(error) =
+            persistent_Manager::flush_persistent_file(policy == AppendFsync::Everysec)
+        {
+            let message = format!("flushing persistent file failed: {}", error);
+            Logger::log_error_data(&message).write_log_to_file();
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::{
+    cache::{snapshot::Snapshot, Cache},
+    logger::logger_manager::Logger,
+    persistent::snapshot,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+// shared with the log rewrite, a snapshot taken while the log is swapped would point at the wrong offset
+pub static PERSISTENCE_JOB_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+// starts a background snapshot, returns false when a snapshot or log rewrite is already running
+pub fn start_snapshot(cache: Arc<Mutex<Cache>>) -> bool {
+    if PERSISTENCE_JOB_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    std::thread::spawn(move || {
+        // the cache is only held while the state is copied
+        let state = cache.lock().unwrap().capture_snapshot();
+        match snapshot::write_snapshot(&state) {
+            Ok(path) => {
+                let message = format!("snapshot written to {:?}", path);
+                Logger::log_info_data(&message).write_log_to_file();
+            }
+            Err(error) => {
+                let message = format!("snapshot failed: {}", error);
+                Logger::log_error_data(&message).write_log_to_file();
+            }
+        }
+        PERSISTENCE_JOB_IN_PROGRESS.store(false, Ordering::SeqCst);
+    });
+    true
+}
+
+pub fn run_scheduled_snapshots(cache: Arc<Mutex<Cache>>, interval: Duration) {
+    loop {
+        std::thread::sleep(interval);
+        start_snapshot(cache.clone());
+    }
+}
@@ -1,29 +1,82 @@
-use std::path::PathBuf;
-use std::sync::LazyLock;
+use crate::config::Settings;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, OnceLock};
 pub static KNOWN_DIRECTORIES: LazyLock<KnownDirectories> =
     LazyLock::new(|| KnownDirectories::create_all_known_directories());
+pub static CONFIG_FILE: LazyLock<PathBuf> = LazyLock::new(|| KnownDirectories::find_config_file());
+
+// every location can be set on the command line, through these variables or in config.json, in that order
+pub const CONFIG_FILE_ENV: &str = "QUEBRIX_CONFIG";
+pub const DATA_DIRECTORY_ENV: &str = "QUEBRIX_DATA_DIR";
+pub const LOG_DIRECTORY_ENV: &str = "QUEBRIX_LOG_DIR";
+pub const CREDS_DIRECTORY_ENV: &str = "QUEBRIX_CREDS_DIR";
+
+// locations given on the command line
+#[derive(Default)]
+pub struct DirectoryOptions {
+    pub config_file: Option<PathBuf>,
+    pub data_directory: Option<PathBuf>,
+    pub log_directory: Option<PathBuf>,
+    pub creds_directory: Option<PathBuf>,
+}
+
+static DIRECTORY_OPTIONS: OnceLock<DirectoryOptions> = OnceLock::new();
+
+// has to run before the config or any directory is used
+pub fn configure_directories(options: DirectoryOptions) {
+    let _ = DIRECTORY_OPTIONS.set(options);
+}
 
 pub struct KnownDirectories {
-    pub app_root_directory: PathBuf,
     pub log_directory: PathBuf,
     pub data_directory: PathBuf,
     pub persistent_directory: PathBuf,
+    pub snapshot_directory: PathBuf,
     pub creds_directory: PathBuf,
 }
 
 impl KnownDirectories {
     pub fn create_all_known_directories() -> KnownDirectories {
         // !!!Important!!!: do not use Logger in this scope, using Logger in this scoped will create deadlock
-        let app_root_directory = KnownDirectories::get_app_root();
-        let log_directory = PathBuf::from(&app_root_directory).join("logs");
-        let data_directory = PathBuf::from(&app_root_directory).join("data");
+        let options = DIRECTORY_OPTIONS.get_or_init(DirectoryOptions::default);
+        let settings = Settings::new();
+        let defaults = KnownDirectories::default_directories(&CONFIG_FILE);
+        // relative paths in config.json are taken from the directory of the config file
+        let config_directory = CONFIG_FILE.parent().unwrap_or(Path::new(".")).to_path_buf();
+        let choose =
+            |option: &Option<PathBuf>, env: &str, setting: &Option<String>, default: PathBuf| {
+                option
+                    .clone()
+                    .or_else(|| std::env::var_os(env).map(PathBuf::from))
+                    .or_else(|| setting.as_ref().map(|path| config_directory.join(path)))
+                    .unwrap_or(default)
+            };
+        let log_directory = choose(
+            &options.log_directory,
+            LOG_DIRECTORY_ENV,
+            &settings.log_directory,
+            defaults.log_directory,
+        );
+        let data_directory = choose(
+            &options.data_directory,
+            DATA_DIRECTORY_ENV,
+            &settings.data_directory,
+            defaults.data_directory,
+        );
         let persistent_directory = PathBuf::from(&data_directory).join("persistent");
-        let creds_directory = PathBuf::from(&app_root_directory).join("creds");
+        let snapshot_directory = PathBuf::from(&data_directory).join("snapshot");
+        let creds_directory = choose(
+            &options.creds_directory,
+            CREDS_DIRECTORY_ENV,
+            &settings.creds_directory,
+            defaults.creds_directory,
+        );
 
         let known_directory_vec = vec![
             &log_directory,
             &data_directory,
             &persistent_directory,
+            &snapshot_directory,
             &creds_directory,
         ];
         known_directory_vec.iter().for_each(|&directory| {
@@ -48,10 +101,10 @@ impl KnownDirectories {
         });
 
         KnownDirectories {
-            app_root_directory,
             data_directory,
             log_directory,
             persistent_directory,
+            snapshot_directory,
             creds_directory,
         }
     }
@@ -60,4 +113,96 @@ impl KnownDirectories {
         file_dir_path.pop();
         return file_dir_path;
     }
+
+    // an explicit path wins, otherwise the first config found next to the executable,
+    // in the XDG config directory or under /etc
+    pub fn find_config_file() -> PathBuf {
+        let options = DIRECTORY_OPTIONS.get_or_init(DirectoryOptions::default);
+        if let Some(config_file) = options
+            .config_file
+            .clone()
+            .or_else(|| std::env::var_os(CONFIG_FILE_ENV).map(PathBuf::from))
+        {
+            return config_file;
+        }
+        let mut candidates = vec![KnownDirectories::get_app_root()
+            .join("config")
+            .join("config.json")];
+        if let Some(config_home) = xdg_directory("XDG_CONFIG_HOME", ".config") {
+            candidates.push(config_home.join("quebrix").join("config.json"));
+        }
+        if cfg!(unix) {
+            candidates.push(PathBuf::from("/etc/quebrix/config.json"));
+        }
+        candidates
+            .iter()
+            .find(|candidate| candidate.exists())
+            .unwrap_or(&candidates[0])
+            .clone()
+    }
+
+    // the default layout follows where the config was found: next to the executable everything stays
+    // next to it, a config under /etc/<name> uses /var/lib/<name> and /var/log/<name>,
+    // one in the XDG config directory uses the XDG data and state directories,
+    // and any other config keeps its instance in the directory it is in
+    fn default_directories(config_file: &Path) -> DefaultDirectories {
+        let config_directory = config_file.parent().unwrap_or(Path::new("."));
+        let instance = config_directory
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "quebrix".to_string());
+        let app_root = KnownDirectories::get_app_root();
+        let xdg_config = xdg_directory("XDG_CONFIG_HOME", ".config");
+
+        if config_directory == app_root.join("config") {
+            DefaultDirectories::under(&app_root)
+        } else if cfg!(unix) && config_directory.parent() == Some(Path::new("/etc")) {
+            let data_directory = PathBuf::from("/var/lib").join(&instance);
+            DefaultDirectories {
+                log_directory: PathBuf::from("/var/log").join(&instance),
+                creds_directory: data_directory.join("creds"),
+                data_directory,
+            }
+        } else if xdg_config.as_deref() == config_directory.parent() {
+            let data_directory = xdg_directory("XDG_DATA_HOME", ".local/share")
+                .unwrap_or_else(|| config_directory.to_path_buf())
+                .join(&instance);
+            let log_directory = xdg_directory("XDG_STATE_HOME", ".local/state")
+                .unwrap_or_else(|| config_directory.to_path_buf())
+                .join(&instance)
+                .join("logs");
+            DefaultDirectories {
+                log_directory,
+                creds_directory: data_directory.join("creds"),
+                data_directory,
+            }
+        } else {
+            DefaultDirectories::under(config_directory)
+        }
+    }
+}
+
+struct DefaultDirectories {
+    log_directory: PathBuf,
+    data_directory: PathBuf,
+    creds_directory: PathBuf,
+}
+
+impl DefaultDirectories {
+    // the layout of a portable install, logs, data and creds side by side
+    fn under(root: &Path) -> Self {
+        DefaultDirectories {
+            log_directory: root.join("logs"),
+            data_directory: root.join("data"),
+            creds_directory: root.join("creds"),
+        }
+    }
+}
+
+// an XDG base directory from its variable, or its default under the home directory
+fn xdg_directory(variable: &str, default: &str) -> Option<PathBuf> {
+    std::env::var_os(variable)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(default)))
 }
@@ -2,13 +2,20 @@ use crate::public_api::server;
 use memory_handling::memory_handling::MemoryHandler;
 use std::sync::{Arc, Mutex};
 mod cache;
+mod cli;
 mod convert;
 mod creds;
 mod crypto;
 mod jobs;
 pub mod known_directories;
 mod persistent;
+use crate::jobs::cluster_expiry_job;
+use crate::jobs::log_rewrite_job;
+use crate::jobs::persistent_flush_job;
 use crate::jobs::retention_policy_job;
+use crate::jobs::snapshot_job;
+use crate::persistent::encryption;
+use crate::persistent::persistent_Manager::{self, AppendFsync};
 use crate::logger::logger_manager::Logger;
 mod config;
 mod memory_handling;
@@ -16,9 +23,14 @@ mod public_api;
 use creds::cred_manager::CredsManager;
 mod logger;
 use crate::config::Settings;
+use cache::script::ScriptLimits;
 use cache::Cache;
 
 fn main() {
+    let args = cli::configure_directories(std::env::args().skip(1).collect());
+    if cli::run(&args) {
+        return;
+    }
     print_qbx();
 
     // Reading configurations from config.json
@@ -30,19 +42,15 @@ fn main() {
     } else {
         let log = Logger::log_info("application starting...");
         log.write_log_to_file();
-        let memory_handler = Arc::new(Mutex::new(MemoryHandler::new()));
         let cred_manager = Arc::new(Mutex::new(CredsManager::new(settings.enable_logger)));
         let memory_log = Logger::log_info("access to memory handling ...");
         memory_log.write_log_to_file();
-        let cache = Arc::new(Mutex::new(Cache::new(
-            settings.port,
-            memory_handler.clone(),
-            settings.eviction_strategy,
-            settings.enable_logger,
-            settings.persistent,
-            cred_manager.clone(),
-        )));
+        let append_fsync = settings.appendfsync.unwrap_or_default();
+        let cache = Arc::new(Mutex::new(build_cache(&settings, cred_manager.clone())));
         let cache_clone = Arc::clone(&cache);
+        let expiry_cache = Arc::clone(&cache);
+        let snapshot_cache = Arc::clone(&cache);
+        let rewrite_cache = Arc::clone(&cache);
         let cred_clone = Arc::clone(&cred_manager);
         let cache_log = Logger::log_info("cache successfully installed ...");
         cache_log.write_log_to_file();
@@ -55,6 +63,26 @@ fn main() {
                 .unwrap_or_else(|_| std::time::Duration::from_secs(0));
             std::thread::sleep(sleep_duration);
         });
+        std::thread::spawn(move || cluster_expiry_job::run_cluster_expiry(expiry_cache));
+        if settings.persistent && append_fsync != AppendFsync::Always {
+            std::thread::spawn(move || persistent_flush_job::run_persistent_flush(append_fsync));
+        }
+        let snapshot_interval = settings.snapshot_interval.unwrap_or(0);
+        if settings.persistent && snapshot_interval > 0 {
+            std::thread::spawn(move || {
+                snapshot_job::run_scheduled_snapshots(
+                    snapshot_cache,
+                    std::time::Duration::from_secs(snapshot_interval),
+                )
+            });
+        }
+        let log_rewrite_growth = settings.log_rewrite_growth.unwrap_or(0);
+        if settings.persistent && log_rewrite_growth > 0 {
+            let min_size = settings.log_rewrite_min_size.unwrap_or(64) * 1024 * 1024;
+            std::thread::spawn(move || {
+                log_rewrite_job::run_log_rewrite_checks(rewrite_cache, min_size, log_rewrite_growth)
+            });
+        }
         std::thread::spawn(move || {
             actix_web::rt::System::new().block_on(async move {
                 let settings: Settings = Settings::new();
@@ -77,6 +105,43 @@ fn main() {
     }
 }
 
+// replays the persisted data into a new cache, shared by the server and the command line tools
+pub fn build_cache(settings: &Settings, cred_manager: Arc<Mutex<CredsManager>>) -> Cache {
+    persistent_Manager::configure_append_fsync(settings.appendfsync.unwrap_or_default());
+    match encryption::keyring_from_settings(settings) {
+        Ok(keyring) => encryption::configure_encryption(keyring),
+        // starting without the key would write plain data next to sealed data, so it does not start at all
+        Err(error) => {
+            let message = format!("invalid encryption settings: {}", error);
+            println!("{}", message);
+            Logger::log_error(&message).write_log_to_file();
+            std::process::exit(1);
+        }
+    }
+    let mut cache = Cache::new(
+        settings.port,
+        Arc::new(Mutex::new(MemoryHandler::new())),
+        settings.eviction_strategy,
+        settings.enable_logger,
+        settings.persistent,
+        cred_manager,
+        ScriptLimits {
+            max_operations: settings.script_max_operations.unwrap_or(1_000_000),
+            timeout: std::time::Duration::from_millis(settings.script_timeout.unwrap_or(5000)),
+        },
+    );
+    if settings.persistent {
+        // writes made on top of a log that can not be replayed to its end would be lost on the next start
+        if let Err(error) = cache.initialize_from_commands() {
+            let message = format!("can not recover from the persistence log: {}", error);
+            println!("{}", message);
+            Logger::log_error(&message).write_log_to_file();
+            std::process::exit(1);
+        }
+    }
+    cache
+}
+
 fn print_qbx() {
     println!(
         "
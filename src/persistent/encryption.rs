@@ -0,0 +1,41 @@
+use crate::config::Settings;
+use crate::crypto::crypto_service::{Keyring, SealingKey};
+use std::sync::OnceLock;
+
+// takes precedence over the key file and the config
+pub const ENCRYPTION_KEY_ENV: &str = "QUEBRIX_ENCRYPTION_KEY";
+
+static KEYRING: OnceLock<Keyring> = OnceLock::new();
+
+pub fn configure_encryption(keyring: Keyring) {
+    let _ = KEYRING.set(keyring);
+}
+
+// without a configured key records and snapshots are written in plain
+pub fn keyring() -> &'static Keyring {
+    KEYRING.get_or_init(|| Keyring::new(None, Vec::new()))
+}
+
+// the current key comes from the environment, the key file or the config, in that order.
+// to rotate, the new key becomes the current one and the old key moves to `encryption_previous_keys`,
+// the next log rewrite seals everything with the new key after which the old one can be dropped.
+// moving the key there without a new one turns encryption off the same way
+pub fn keyring_from_settings(settings: &Settings) -> Result<Keyring, String> {
+    let current = match std::env::var(ENCRYPTION_KEY_ENV) {
+        Ok(key) if !key.trim().is_empty() => Some(key),
+        _ => match &settings.encryption_key_file {
+            Some(path) => Some(std::fs::read_to_string(path).map_err(|error| {
+                format!("can not read encryption key file {}: {}", path, error)
+            })?),
+            None => settings.encryption_key.clone(),
+        },
+    };
+    let current = current.map(|key| SealingKey::from_hex(&key)).transpose()?;
+    let previous = settings
+        .encryption_previous_keys
+        .iter()
+        .flatten()
+        .map(|key| SealingKey::from_hex(key))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Keyring::new(current, previous))
+}
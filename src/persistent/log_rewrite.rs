@@ -0,0 +1,129 @@
+use crate::known_directories::KNOWN_DIRECTORIES;
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+};
+
+use super::{
+    persistent_Manager,
+    record::{self, PersistentRecord},
+    snapshot::SnapshotState,
+};
+
+pub struct RewriteResult {
+    pub commands: usize,
+    pub size_before: u64,
+    pub size_after: u64,
+    // where the rewritten state ends and the commands logged during the rewrite begin
+    pub rewritten_offset: u64,
+}
+
+// the smallest command list that rebuilds `state` when replayed on an empty cache
+pub fn rewrite_commands(state: &SnapshotState) -> Vec<PersistentRecord> {
+    let mut commands = Vec::new();
+    for cluster in state.clusters.iter() {
+        commands.push(PersistentRecord::new("SET_CLUSTER").arg(&cluster.name));
+        for entry in cluster.entries.iter() {
+            // value type 2 is an integer
+            let command = if entry.value_type == 2 {
+                "SET_INT"
+            } else {
+                "SET"
+            };
+            commands.push(
+                PersistentRecord::new(command)
+                    .arg(&cluster.name)
+                    .arg(&entry.key)
+                    .arg(&entry.value),
+            );
+            if let Some(expires_at) = entry.expires_at {
+                commands.push(
+                    PersistentRecord::new("EXPIRE")
+                        .arg(&cluster.name)
+                        .arg(&entry.key)
+                        .arg(expires_at.to_string()),
+                );
+            }
+            if !entry.tags.is_empty() {
+                commands.push(
+                    entry.tags.iter().fold(
+                        PersistentRecord::new("TAG")
+                            .arg(&cluster.name)
+                            .arg(&entry.key),
+                        |record, tag| record.arg(tag),
+                    ),
+                );
+            }
+        }
+    }
+    // configs go after the data so a read only cluster can still be filled on replay
+    for (cluster, config) in state.cluster_configs.iter() {
+        commands.push(
+            PersistentRecord::new("CLUSTER_CONFIG")
+                .arg(cluster)
+                .arg(config),
+        );
+    }
+    for (alias, target) in state.aliases.iter() {
+        commands.push(
+            PersistentRecord::new("ALIAS")
+                .arg(alias)
+                .arg(target)
+                .arg("false"),
+        );
+    }
+    for (cluster, expires_at) in state.cluster_expirations.iter() {
+        commands.push(
+            PersistentRecord::new("EXPIRE_CLUSTER")
+                .arg(cluster)
+                .arg(expires_at.to_string()),
+        );
+    }
+    // the commands stand for the state at capture, so a point in time replay treats them as written then
+    commands
+        .into_iter()
+        .map(|command| command.with_timestamp(state.created_at))
+        .collect()
+}
+
+// replaces the log with the commands of `state` followed by anything written since it was captured,
+// files from earlier days are covered by the rewrite and removed
+pub fn rewrite_log(state: &SnapshotState) -> io::Result<RewriteResult> {
+    let position = &state.log_position;
+    let size_before = persistent_Manager::persistent_log_size();
+    let rewritten_path = KNOWN_DIRECTORIES
+        .persistent_directory
+        .join(format!("{}.rewrite", position.file));
+
+    let commands = rewrite_commands(state);
+    {
+        let mut writer = BufWriter::new(File::create(&rewritten_path)?);
+        record::write_header(&mut writer)?;
+        for command in commands.iter() {
+            writer.write_all(&command.to_frame())?;
+        }
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+    }
+    let rewritten_offset = fs::metadata(&rewritten_path)?.len();
+
+    if let Err(error) = persistent_Manager::swap_rewritten_file(&rewritten_path, position) {
+        let _ = fs::remove_file(&rewritten_path);
+        return Err(error);
+    }
+
+    if let Some(rewritten_date) = persistent_Manager::persistent_file_date(&position.file) {
+        for (date, path) in persistent_Manager::persistent_files() {
+            if date < rewritten_date {
+                fs::remove_file(path)?;
+            }
+        }
+    }
+
+    Ok(RewriteResult {
+        commands: commands.len(),
+        size_before,
+        size_after: persistent_Manager::persistent_log_size(),
+        rewritten_offset,
+    })
+}
@@ -1 +1,5 @@
-pub mod persistent_Manager;
\ No newline at end of file
+pub mod encryption;
+pub mod log_rewrite;
+pub mod persistent_Manager;
+pub mod record;
+pub mod snapshot;
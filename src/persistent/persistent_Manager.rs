@@ -1,15 +1,162 @@
 use crate::{known_directories::KNOWN_DIRECTORIES, logger::logger_manager::Logger};
 use chrono::prelude::*;
-use std::{env, fs::OpenOptions, io::Write, path::PathBuf};
+use serde::Deserialize;
+use std::{
+    env,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, ErrorKind, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
 
-pub fn write_to_persistent_file(command: &String) -> std::io::Result<()> {
-    let kn_dir = &KNOWN_DIRECTORIES;
+use super::record::{self, PersistentRecord, ReadRecord, RecordReader};
+
+// when appended records are forced to disk
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AppendFsync {
+    // every record is flushed and synced before the write returns
+    Always,
+    // records are buffered, flushed and synced once a second by the background flusher
+    #[default]
+    Everysec,
+    // records are buffered and handed to the OS once a second, the OS decides when they reach the disk
+    No,
+}
+
+// the file records are currently appended to, kept open between writes
+struct PersistentWriter {
+    file_name: String,
+    writer: BufWriter<File>,
+}
+
+// also held while a log rewrite swaps the file so no write is lost in between
+static PERSISTENT_WRITER: Mutex<Option<PersistentWriter>> = Mutex::new(None);
+static APPEND_FSYNC: OnceLock<AppendFsync> = OnceLock::new();
+
+pub fn configure_append_fsync(policy: AppendFsync) {
+    let _ = APPEND_FSYNC.set(policy);
+}
+
+pub fn append_fsync() -> AppendFsync {
+    APPEND_FSYNC.get().copied().unwrap_or_default()
+}
+
+// a point in the persistence log, everything written before it is covered by a snapshot
+#[derive(Clone, Debug)]
+pub struct LogPosition {
+    pub file: String,
+    pub offset: u64,
+}
+
+pub fn persistent_file_name(date: NaiveDate) -> String {
+    format!("persistent_{}.qbx", date.format("%d-%m-%Y"))
+}
+
+pub fn persistent_file_date(file_name: &str) -> Option<NaiveDate> {
+    let date = file_name
+        .strip_prefix("persistent_")?
+        .strip_suffix(".qbx")?;
+    NaiveDate::parse_from_str(date, "%d-%m-%Y").ok()
+}
+
+// every persistent file on disk ordered from the oldest day to the newest
+pub fn persistent_files() -> Vec<(NaiveDate, PathBuf)> {
+    let mut files: Vec<(NaiveDate, PathBuf)> =
+        std::fs::read_dir(&KNOWN_DIRECTORIES.persistent_directory)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter_map(|entry| {
+                        let name = entry.file_name().into_string().ok()?;
+                        Some((persistent_file_date(&name)?, entry.path()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    files
+}
+
+// size of every persistent file on disk together
+pub fn persistent_log_size() -> u64 {
+    persistent_files()
+        .iter()
+        .filter_map(|(_, path)| std::fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+// appends whatever was logged after `position` to the rewritten file and puts it in place of that log,
+// writers only wait while this tail is copied
+pub fn swap_rewritten_file(rewritten: &Path, position: &LogPosition) -> std::io::Result<()> {
+    let mut persistent_writer = PERSISTENT_WRITER.lock().unwrap();
+    // buffered records belong to the tail, the writer is reopened on the new file by the next write
+    if let Some(mut current) = persistent_writer.take() {
+        current.writer.flush()?;
+    }
+    let log_path = KNOWN_DIRECTORIES.persistent_directory.join(&position.file);
+    if let Ok(mut log) = File::open(&log_path) {
+        log.seek(SeekFrom::Start(position.offset))?;
+        let mut rewritten_file = OpenOptions::new().append(true).open(rewritten)?;
+        std::io::copy(&mut log, &mut rewritten_file)?;
+        rewritten_file.sync_all()?;
+    }
+    std::fs::rename(rewritten, &log_path)
+}
+
+pub fn current_log_position() -> LogPosition {
+    // buffered records are counted in the position, so they have to be in the file first
+    let _ = flush_persistent_file(false);
+    let file = persistent_file_name(Local::now().date_naive());
+    let offset = std::fs::metadata(KNOWN_DIRECTORIES.persistent_directory.join(&file))
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+    LogPosition { file, offset }
+}
+
+pub fn write_to_persistent_file(record: &PersistentRecord) -> std::io::Result<()> {
+    let mut persistent_writer = PERSISTENT_WRITER.lock().unwrap();
 
     let now: DateTime<Local> = Local::now();
-    let persistent_file_name = format!("persistent_{}.qbx", now.format("%d-%m-%Y"));
-    let persistent_file_path =
-        PathBuf::from(&kn_dir.persistent_directory).join(&persistent_file_name);
+    let persistent_file_name = persistent_file_name(now.date_naive());
+    // the first write of a day moves on to that day's file
+    if persistent_writer
+        .as_ref()
+        .map_or(true, |current| current.file_name != persistent_file_name)
+    {
+        if let Some(mut previous) = persistent_writer.take() {
+            previous.writer.flush()?;
+            previous.writer.get_ref().sync_data()?;
+        }
+        *persistent_writer = Some(open_persistent_writer(persistent_file_name)?);
+    }
 
+    let current = persistent_writer.as_mut().unwrap();
+    current.writer.write_all(&record.to_frame())?;
+    if append_fsync() == AppendFsync::Always {
+        current.writer.flush()?;
+        current.writer.get_ref().sync_data()?;
+    }
+    let message = format!("command:{} set in persistent", record.command());
+    let command_log = Logger::log_info_data(&message);
+    command_log.write_log_to_file();
+    Ok(())
+}
+
+// hands buffered records to the OS and with `sync` waits until they are on disk
+pub fn flush_persistent_file(sync: bool) -> std::io::Result<()> {
+    if let Some(current) = PERSISTENT_WRITER.lock().unwrap().as_mut() {
+        current.writer.flush()?;
+        if sync {
+            current.writer.get_ref().sync_data()?;
+        }
+    }
+    Ok(())
+}
+
+fn open_persistent_writer(file_name: String) -> std::io::Result<PersistentWriter> {
+    let persistent_file_path = KNOWN_DIRECTORIES.persistent_directory.join(&file_name);
     if !persistent_file_path.exists() {
         let set_log = Logger::log_info("persistent file is not exist create file ...");
         set_log.write_log_to_file();
@@ -18,10 +165,161 @@ pub fn write_to_persistent_file(command: &String) -> std::io::Result<()> {
         log.write_log_to_file();
     }
     let mut file = OpenOptions::new().append(true).open(persistent_file_path)?;
-    let formated_command = format!("{}", command.clone());
-    writeln!(file, "{}", formated_command)?;
-    let message = format!("command:{} set in persistent", command.clone());
-    let command_log = Logger::log_info_data(&message);
-    command_log.write_log_to_file();
-    Ok(())
+    if file.metadata()?.len() == 0 {
+        record::write_header(&mut file)?;
+    }
+    Ok(PersistentWriter {
+        file_name,
+        writer: BufWriter::new(file),
+    })
+}
+
+pub struct MigrationResult {
+    pub records: usize,
+    pub skipped: usize,
+    // the offset asked for, moved to where the same command sits in the migrated file
+    pub offset: Option<u64>,
+}
+
+// writes the records of a file being migrated and follows where the asked for offset ends up
+struct Migration {
+    writer: BufWriter<File>,
+    new_offset: u64,
+    offset: Option<u64>,
+    result: MigrationResult,
+}
+
+impl Migration {
+    // called with the old offset of every record before it is written
+    fn reached(&mut self, old_offset: u64) {
+        if self.offset.map_or(false, |offset| offset <= old_offset) && self.result.offset.is_none()
+        {
+            self.result.offset = Some(self.new_offset);
+        }
+    }
+
+    fn write(&mut self, record: &PersistentRecord) -> std::io::Result<()> {
+        let frame = record.to_frame();
+        self.writer.write_all(&frame)?;
+        self.new_offset += frame.len() as u64;
+        self.result.records += 1;
+        Ok(())
+    }
+}
+
+// unix milliseconds of the oldest record still on disk, None when there is none.
+// a log rewrite and the retention policy remove older history, so nothing before this can be rebuilt
+pub fn history_start() -> Option<u64> {
+    for (date, path) in persistent_files() {
+        let Ok(mut file) = File::open(&path) else {
+            continue;
+        };
+        let Ok(Some(version)) = record::read_header(&mut file) else {
+            continue;
+        };
+        let mut reader = RecordReader::new(BufReader::new(file), record::LOG_HEADER_LEN)
+            .with_version(version, day_start_millis(date));
+        loop {
+            match reader.next_record() {
+                Ok(ReadRecord::Record(record)) => return Some(record.timestamp()),
+                Ok(ReadRecord::Corrupt) => {}
+                _ => break,
+            }
+        }
+    }
+    None
+}
+
+// unix milliseconds of the local midnight starting `date`
+pub fn day_start_millis(date: NaiveDate) -> u64 {
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+        .map_or(0, |midnight| midnight.timestamp_millis().max(0) as u64)
+}
+
+// rewrites a persistent file of the old text format or an older record version as current records,
+// None when it is already current. the old formats carry no time, so their records count as
+// written at the start of the file's day
+pub fn migrate_persistent_file(
+    path: &Path,
+    offset: Option<u64>,
+) -> std::io::Result<Option<MigrationResult>> {
+    let mut file = File::open(path)?;
+    let version = record::read_header(&mut file)?;
+    if version == Some(record::LOG_VERSION) {
+        return Ok(None);
+    }
+    let written_at = path
+        .file_name()
+        .and_then(|name| persistent_file_date(&name.to_string_lossy()))
+        .map_or(0, day_start_millis);
+
+    let migrated_path = path.with_extension("qbx.migrate");
+    let mut migration = Migration {
+        writer: BufWriter::new(File::create(&migrated_path)?),
+        new_offset: record::LOG_HEADER_LEN,
+        offset,
+        result: MigrationResult {
+            records: 0,
+            skipped: 0,
+            offset: None,
+        },
+    };
+    record::write_header(&mut migration.writer)?;
+    match version {
+        Some(version) => {
+            let mut reader = RecordReader::new(BufReader::new(file), record::LOG_HEADER_LEN)
+                .with_version(version, written_at);
+            loop {
+                migration.reached(reader.offset);
+                match reader.next_record()? {
+                    ReadRecord::Record(record) => migration.write(&record)?,
+                    ReadRecord::Corrupt => migration.result.skipped += 1,
+                    ReadRecord::Damaged => {
+                        return Err(std::io::Error::new(
+                            ErrorKind::InvalidData,
+                            format!("damaged frame header at {}", reader.offset),
+                        ))
+                    }
+                    // a torn record at the end is dropped, it would be truncated on replay anyway
+                    ReadRecord::Torn | ReadRecord::End => break,
+                }
+            }
+        }
+        None => {
+            file.seek(SeekFrom::Start(0))?;
+            let mut reader = BufReader::new(file);
+            let mut old_offset = 0u64;
+            let mut line = Vec::new();
+            loop {
+                migration.reached(old_offset);
+                line.clear();
+                let read = reader.read_until(b'\n', &mut line)?;
+                if read == 0 {
+                    break;
+                }
+                old_offset += read as u64;
+                let text = String::from_utf8_lossy(&line);
+                if text.trim().is_empty() {
+                    continue;
+                }
+                match PersistentRecord::from_legacy_line(&text) {
+                    Some(record) => migration.write(&record.with_timestamp(written_at))?,
+                    None => {
+                        let message =
+                            format!("legacy command:{:?} can not be migrated", text.trim());
+                        Logger::log_warn_data(&message).write_log_to_file();
+                        migration.result.skipped += 1;
+                    }
+                }
+            }
+        }
+    }
+    if offset.is_some() && migration.result.offset.is_none() {
+        migration.result.offset = Some(migration.new_offset);
+    }
+    migration.writer.flush()?;
+    migration.writer.get_ref().sync_all()?;
+    std::fs::rename(&migrated_path, path)?;
+    Ok(Some(migration.result))
 }
@@ -0,0 +1,477 @@
+use crate::crypto::crypto_service::OpenError;
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::encryption;
+
+// persistent files start with this header, files without it are the old text format
+pub const LOG_MAGIC: &[u8; 6] = b"QBXLOG";
+// version 3 stamps every record with the time it was written, version 2 records carry no time.
+// version 4 starts every payload with a flag telling whether the rest is sealed,
+// version 5 guards the frame header with a crc32 of its own
+pub const LOG_VERSION: u8 = 5;
+const SEALED_LOG_VERSION: u8 = 4;
+const TIMED_LOG_VERSION: u8 = 3;
+const UNTIMED_LOG_VERSION: u8 = 2;
+const PLAIN_RECORD: u8 = 0;
+const SEALED_RECORD: u8 = 1;
+pub const LOG_HEADER_LEN: u64 = (LOG_MAGIC.len() + 1) as u64;
+// a record frame is the payload length, the crc32 of the payload and the crc32 of those two,
+// followed by the payload. frames before version 5 have no crc32 of the header
+const FRAME_HEADER_LEN: u64 = 12;
+const UNGUARDED_FRAME_HEADER_LEN: u64 = 8;
+
+// one persisted command, the command name followed by its arguments as raw bytes,
+// so clusters, keys and values can hold spaces, brackets or anything else
+#[derive(Clone, Debug, PartialEq)]
+pub struct PersistentRecord {
+    command: String,
+    args: Vec<Vec<u8>>,
+    // unix milliseconds of when the command ran
+    timestamp: u64,
+}
+
+impl PersistentRecord {
+    pub fn new(command: &str) -> Self {
+        PersistentRecord {
+            command: command.to_string(),
+            args: Vec::new(),
+            timestamp: now_millis(),
+        }
+    }
+
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn arg<T: AsRef<[u8]>>(mut self, value: T) -> Self {
+        self.args.push(value.as_ref().to_vec());
+        self
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+
+    pub fn bytes_arg(&self, index: usize) -> Option<&[u8]> {
+        self.args.get(index).map(|arg| arg.as_slice())
+    }
+
+    pub fn str_arg(&self, index: usize) -> Option<&str> {
+        std::str::from_utf8(self.bytes_arg(index)?).ok()
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&self.timestamp.to_le_bytes());
+        put_field(&mut payload, self.command.as_bytes());
+        for arg in self.args.iter() {
+            put_field(&mut payload, arg);
+        }
+        payload
+    }
+
+    // records of an untimed file get `timestamp` instead of a time of their own
+    fn decode(payload: &[u8], version: u8, timestamp: u64) -> Option<Self> {
+        let (timestamp, mut rest) = if version == UNTIMED_LOG_VERSION {
+            (timestamp, payload)
+        } else {
+            let (time, rest) = payload.split_at_checked(8)?;
+            (u64::from_le_bytes(time.try_into().ok()?), rest)
+        };
+        let mut fields = Vec::new();
+        while !rest.is_empty() {
+            let len = u32::from_le_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+            fields.push(rest.get(4..4 + len)?.to_vec());
+            rest = &rest[4 + len..];
+        }
+        let mut fields = fields.into_iter();
+        let command = String::from_utf8(fields.next()?).ok()?;
+        Some(PersistentRecord {
+            command,
+            args: fields.collect(),
+            timestamp,
+        })
+    }
+
+    // the framed record as it is appended to a persistent file, sealed when encryption is configured
+    pub fn to_frame(&self) -> Vec<u8> {
+        let record = self.encode();
+        let mut payload = Vec::with_capacity(record.len() + 1);
+        match encryption::keyring().seal(&record) {
+            Some(sealed) => {
+                payload.push(SEALED_RECORD);
+                payload.extend_from_slice(&sealed);
+            }
+            None => {
+                payload.push(PLAIN_RECORD);
+                payload.extend_from_slice(&record);
+            }
+        }
+        let mut frame = Vec::with_capacity(payload.len() + FRAME_HEADER_LEN as usize);
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+        let header_crc = crc32fast::hash(&frame);
+        frame.extend_from_slice(&header_crc.to_le_bytes());
+        frame.extend_from_slice(&payload);
+        frame
+    }
+
+    // reads a command written by the old text format, None when the line can not be understood
+    pub fn from_legacy_line(line: &str) -> Option<Self> {
+        let line = line.trim().trim_matches('"');
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let command = *parts.first()?;
+        match command {
+            "SET" | "INCR" | "DECR" | "APPEND" | "SETRANGE" => {
+                let (head, rest) = line.split_once('[')?;
+                let (inside, _) = rest.split_once(']')?;
+                let value = if inside.trim().is_empty() {
+                    Vec::new()
+                } else {
+                    inside
+                        .split(',')
+                        .map(|byte| byte.trim().parse::<u8>())
+                        .collect::<Result<Vec<u8>, _>>()
+                        .ok()?
+                };
+                let head: Vec<&str> = head.split_whitespace().collect();
+                let record = head[1..]
+                    .iter()
+                    .fold(PersistentRecord::new(command), |record, part| {
+                        record.arg(part)
+                    });
+                Some(record.arg(value))
+            }
+            // the config is json and may hold spaces of its own
+            "CLUSTER_CONFIG" if parts.len() >= 3 => Some(
+                PersistentRecord::new(command)
+                    .arg(parts[1])
+                    .arg(parts[2..].join(" ")),
+            ),
+            _ => Some(
+                parts[1..]
+                    .iter()
+                    .fold(PersistentRecord::new(command), |record, part| {
+                        record.arg(part)
+                    }),
+            ),
+        }
+    }
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+// ttls are written as wall clock deadlines in unix milliseconds so a replay keeps the original deadline
+pub fn deadline_millis(ttl: Duration) -> u64 {
+    (SystemTime::now() + ttl)
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+// time left until a persisted deadline, None once it has passed
+pub fn remaining_ttl(deadline: u64) -> Option<Duration> {
+    (UNIX_EPOCH + Duration::from_millis(deadline))
+        .duration_since(SystemTime::now())
+        .ok()
+}
+
+pub fn write_header<W: Write>(writer: &mut W) -> io::Result<()> {
+    writer.write_all(LOG_MAGIC)?;
+    writer.write_all(&[LOG_VERSION])
+}
+
+// the record version the reader starts with, None for a file of the old text format
+pub fn read_header<R: Read>(reader: &mut R) -> io::Result<Option<u8>> {
+    let mut header = [0u8; LOG_HEADER_LEN as usize];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error),
+    }
+    if &header[..LOG_MAGIC.len()] != LOG_MAGIC {
+        return Ok(None);
+    }
+    let version = header[LOG_MAGIC.len()];
+    if ![
+        LOG_VERSION,
+        SEALED_LOG_VERSION,
+        TIMED_LOG_VERSION,
+        UNTIMED_LOG_VERSION,
+    ]
+    .contains(&version)
+    {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("unsupported persistent file version {}", version),
+        ));
+    }
+    Ok(Some(version))
+}
+
+pub enum ReadRecord {
+    Record(PersistentRecord),
+    // the frame was complete but its checksum or content is wrong, the next frame can still be read
+    Corrupt,
+    // the frame header does not match its checksum, so where the next frame starts is unknown
+    Damaged,
+    // the file ends in the middle of a frame, usually a write cut short by a crash
+    Torn,
+    End,
+}
+
+// reads framed records one by one and keeps track of the byte offset of the next frame
+pub struct RecordReader<R: Read> {
+    reader: R,
+    version: u8,
+    // given to records of an untimed file
+    untimed_at: u64,
+    pub offset: u64,
+}
+
+impl<R: Read> RecordReader<R> {
+    pub fn new(reader: R, offset: u64) -> Self {
+        RecordReader {
+            reader,
+            version: LOG_VERSION,
+            untimed_at: 0,
+            offset,
+        }
+    }
+
+    // reads a file of an older record version, records without a time of their own get `untimed_at`
+    pub fn with_version(mut self, version: u8, untimed_at: u64) -> Self {
+        self.version = version;
+        self.untimed_at = untimed_at;
+        self
+    }
+
+    pub fn next_record(&mut self) -> io::Result<ReadRecord> {
+        let header_len = if self.version == LOG_VERSION {
+            FRAME_HEADER_LEN
+        } else {
+            UNGUARDED_FRAME_HEADER_LEN
+        };
+        let mut frame_header = [0u8; FRAME_HEADER_LEN as usize];
+        let frame_header = &mut frame_header[..header_len as usize];
+        let read = read_full(&mut self.reader, frame_header)?;
+        if read == 0 {
+            return Ok(ReadRecord::End);
+        }
+        if read < frame_header.len() {
+            return Ok(ReadRecord::Torn);
+        }
+        if self.version == LOG_VERSION {
+            let header_crc = u32::from_le_bytes(frame_header[8..].try_into().unwrap());
+            if crc32fast::hash(&frame_header[..8]) != header_crc {
+                return Ok(ReadRecord::Damaged);
+            }
+        }
+        let len = u32::from_le_bytes(frame_header[..4].try_into().unwrap()) as u64;
+        let crc = u32::from_le_bytes(frame_header[4..8].try_into().unwrap());
+
+        let mut payload = Vec::new();
+        (&mut self.reader).take(len).read_to_end(&mut payload)?;
+        if (payload.len() as u64) < len {
+            return Ok(ReadRecord::Torn);
+        }
+        self.offset += header_len + len;
+
+        if crc32fast::hash(&payload) != crc {
+            return Ok(ReadRecord::Corrupt);
+        }
+        let payload = if self.version >= SEALED_LOG_VERSION {
+            match unseal(&payload)? {
+                Some(payload) => payload,
+                None => return Ok(ReadRecord::Corrupt),
+            }
+        } else {
+            payload
+        };
+        match PersistentRecord::decode(&payload, self.version, self.untimed_at) {
+            Some(record) => Ok(ReadRecord::Record(record)),
+            None => Ok(ReadRecord::Corrupt),
+        }
+    }
+}
+
+// the record inside a payload of version 4 or later, None when it does not hold up.
+// a record sealed with a key that is not configured is an error, not damage, so it is never skipped
+fn unseal(payload: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    match payload.split_first() {
+        Some((&PLAIN_RECORD, record)) => Ok(Some(record.to_vec())),
+        Some((&SEALED_RECORD, sealed)) => match encryption::keyring().open(sealed) {
+            Ok(record) => Ok(Some(record)),
+            Err(OpenError::Rejected) => Ok(None),
+            Err(OpenError::UnknownKey(id)) => Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("record sealed with unknown encryption key {}", id),
+            )),
+        },
+        _ => Ok(None),
+    }
+}
+
+fn put_field(payload: &mut Vec<u8>, field: &[u8]) {
+    payload.extend_from_slice(&(field.len() as u32).to_le_bytes());
+    payload.extend_from_slice(field);
+}
+
+// like read_exact but reports how much was read when the input ends early
+fn read_full<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+    while read < buffer.len() {
+        match reader.read(&mut buffer[read..]) {
+            Ok(0) => break,
+            Ok(count) => read += count,
+            Err(error) if error.kind() == ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::crypto_service::{Keyring, SealingKey};
+    use std::io::Cursor;
+
+    const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+    const OTHER_KEY: &str = "1f1e1d1c1b1a191817161514131211100f0e0d0c0b0a09080706050403020100";
+
+    fn record() -> PersistentRecord {
+        PersistentRecord::new("SET")
+            .arg("my cluster")
+            .arg("key [1, 2]")
+            .arg([0xff, 0x00, 0x80, b' ', b'['])
+            .with_timestamp(1_700_000_000_000)
+    }
+
+    fn reader(bytes: Vec<u8>) -> RecordReader<Cursor<Vec<u8>>> {
+        RecordReader::new(Cursor::new(bytes), 0)
+    }
+
+    #[test]
+    fn frame_round_trips_raw_args() {
+        let mut bytes = record().to_frame();
+        bytes.extend_from_slice(&PersistentRecord::new("DEL").arg("c").arg("k").to_frame());
+        let mut reader = reader(bytes.clone());
+
+        match reader.next_record().unwrap() {
+            ReadRecord::Record(read) => {
+                assert_eq!(read, record());
+                assert_eq!(read.str_arg(1), Some("key [1, 2]"));
+                assert_eq!(read.str_arg(2), None);
+            }
+            _ => panic!("expected a record"),
+        }
+        assert!(
+            matches!(reader.next_record().unwrap(), ReadRecord::Record(read) if read.command() == "DEL")
+        );
+        assert!(matches!(reader.next_record().unwrap(), ReadRecord::End));
+        assert_eq!(reader.offset, bytes.len() as u64);
+    }
+
+    #[test]
+    fn payload_crc_mismatch_is_corrupt_and_skipped() {
+        let mut bytes = record().to_frame();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0x01;
+        let first_len = bytes.len() as u64;
+        bytes.extend_from_slice(&PersistentRecord::new("DEL").arg("c").arg("k").to_frame());
+        let mut reader = reader(bytes);
+
+        assert!(matches!(reader.next_record().unwrap(), ReadRecord::Corrupt));
+        assert_eq!(reader.offset, first_len);
+        assert!(
+            matches!(reader.next_record().unwrap(), ReadRecord::Record(read) if read.command() == "DEL")
+        );
+    }
+
+    #[test]
+    fn header_crc_mismatch_is_damaged() {
+        let mut bytes = record().to_frame();
+        bytes[0] ^= 0x01;
+        let mut reader = reader(bytes);
+
+        assert!(matches!(reader.next_record().unwrap(), ReadRecord::Damaged));
+        assert_eq!(reader.offset, 0);
+    }
+
+    #[test]
+    fn truncated_tail_is_torn() {
+        let first = record().to_frame();
+        let mut bytes = first.clone();
+        bytes.extend_from_slice(&record().to_frame());
+        bytes.truncate(bytes.len() - 3);
+        let mut reader = reader(bytes);
+
+        assert!(matches!(
+            reader.next_record().unwrap(),
+            ReadRecord::Record(_)
+        ));
+        assert!(matches!(reader.next_record().unwrap(), ReadRecord::Torn));
+        assert_eq!(reader.offset, first.len() as u64);
+
+        let mut reader = self::reader(first[..5].to_vec());
+        assert!(matches!(reader.next_record().unwrap(), ReadRecord::Torn));
+    }
+
+    #[test]
+    fn legacy_set_line_keeps_the_value_bytes() {
+        let read = PersistentRecord::from_legacy_line("SET c k [1, 2]").unwrap();
+
+        assert_eq!(read.command(), "SET");
+        assert_eq!(read.len(), 3);
+        assert_eq!(read.str_arg(0), Some("c"));
+        assert_eq!(read.str_arg(1), Some("k"));
+        assert_eq!(read.bytes_arg(2), Some(&[1u8, 2][..]));
+        assert!(PersistentRecord::from_legacy_line("SET c k [1, 300]").is_none());
+    }
+
+    #[test]
+    fn sealed_record_round_trips_across_a_key_rotation() {
+        let writer = Keyring::new(Some(SealingKey::from_hex(KEY).unwrap()), Vec::new());
+        let sealed = writer.seal(&record().encode()).unwrap();
+
+        let rotated = Keyring::new(
+            Some(SealingKey::from_hex(OTHER_KEY).unwrap()),
+            vec![SealingKey::from_hex(KEY).unwrap()],
+        );
+        let opened = rotated.open(&sealed).ok().unwrap();
+        assert_eq!(
+            PersistentRecord::decode(&opened, LOG_VERSION, 0),
+            Some(record())
+        );
+
+        let mut tampered = sealed.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 0x01;
+        assert!(matches!(rotated.open(&tampered), Err(OpenError::Rejected)));
+
+        let stranger = Keyring::new(Some(SealingKey::from_hex(OTHER_KEY).unwrap()), Vec::new());
+        assert!(matches!(
+            stranger.open(&sealed),
+            Err(OpenError::UnknownKey(_))
+        ));
+        assert!(Keyring::new(None, Vec::new()).seal(b"plain").is_none());
+    }
+}
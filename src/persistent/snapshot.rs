@@ -0,0 +1,334 @@
+use crate::{known_directories::KNOWN_DIRECTORIES, logger::logger_manager::Logger};
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, ErrorKind, Read, Write},
+    path::PathBuf,
+};
+
+use super::{encryption, persistent_Manager::LogPosition};
+use crate::crypto::crypto_service::OpenError;
+
+const SNAPSHOT_MAGIC: &[u8; 7] = b"QBXSNAP";
+const SNAPSHOT_VERSION: u8 = 1;
+// the same content sealed as a whole with the configured encryption key
+const SEALED_SNAPSHOT_VERSION: u8 = 2;
+// older snapshots are removed once a newer one is written
+const SNAPSHOTS_TO_KEEP: usize = 2;
+
+// all times are unix milliseconds so a snapshot stays meaningful across restarts
+#[derive(Clone, Debug)]
+pub struct SnapshotEntry {
+    pub key: String,
+    pub value: Vec<u8>,
+    pub value_type: u8,
+    pub expires_at: Option<u64>,
+    pub ttl: Option<u64>,
+    pub version: u64,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub last_access: u64,
+    pub access_count: u64,
+    pub tags: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SnapshotCluster {
+    pub name: String,
+    pub entries: Vec<SnapshotEntry>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SnapshotState {
+    pub created_at: u64,
+    pub log_position: LogPosition,
+    pub version_seq: u64,
+    pub clusters: Vec<SnapshotCluster>,
+    pub aliases: Vec<(String, String)>,
+    pub cluster_configs: Vec<(String, String)>, // cluster and its config as json
+    pub cluster_expirations: Vec<(String, u64)>,
+}
+
+pub fn write_snapshot(state: &SnapshotState) -> io::Result<PathBuf> {
+    let snapshot_directory = &KNOWN_DIRECTORIES.snapshot_directory;
+    let file_name = format!("snapshot_{}.qbs", state.created_at);
+    let path = snapshot_directory.join(&file_name);
+    let temp_path = snapshot_directory.join(format!("{}.tmp", file_name));
+
+    // written next to the final name first so a crash never leaves a half written snapshot behind
+    {
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        writer.write_all(SNAPSHOT_MAGIC)?;
+        if encryption::keyring().is_enabled() {
+            let mut content = Vec::new();
+            encode_content(&mut content, state)?;
+            let sealed = encryption::keyring()
+                .seal(&content)
+                .ok_or_else(|| io::Error::new(ErrorKind::Other, "snapshot can not be sealed"))?;
+            writer.write_all(&[SEALED_SNAPSHOT_VERSION])?;
+            writer.write_all(&sealed)?;
+        } else {
+            writer.write_all(&[SNAPSHOT_VERSION])?;
+            encode_content(&mut writer, state)?;
+        }
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+    }
+    fs::rename(&temp_path, &path)?;
+
+    for old_snapshot in snapshot_files().into_iter().skip(SNAPSHOTS_TO_KEEP) {
+        let _ = fs::remove_file(old_snapshot);
+    }
+    Ok(path)
+}
+
+// drops every snapshot but `keep`, used when the log they point into has been rewritten
+pub fn remove_snapshots_except(keep: &PathBuf) {
+    for snapshot in snapshot_files() {
+        if &snapshot != keep {
+            let _ = fs::remove_file(snapshot);
+        }
+    }
+}
+
+// newest snapshot that can be read, falling back to older ones when the latest is damaged
+pub fn load_latest_snapshot() -> Option<SnapshotState> {
+    for path in snapshot_files() {
+        let result = File::open(&path).and_then(|file| decode_snapshot(&mut BufReader::new(file)));
+        match result {
+            Ok(state) => return Some(state),
+            Err(error) => {
+                let message = format!("can not read snapshot {:?}: {}", path, error);
+                Logger::log_error_data(&message).write_log_to_file();
+            }
+        }
+    }
+    None
+}
+
+// snapshot files ordered from newest to oldest
+fn snapshot_files() -> Vec<PathBuf> {
+    let mut files: Vec<(u64, PathBuf)> = fs::read_dir(&KNOWN_DIRECTORIES.snapshot_directory)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let name = entry.file_name().into_string().ok()?;
+                    let created_at = name
+                        .strip_prefix("snapshot_")?
+                        .strip_suffix(".qbs")?
+                        .parse::<u64>()
+                        .ok()?;
+                    Some((created_at, entry.path()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    files.into_iter().map(|(_, path)| path).collect()
+}
+
+fn encode_content<W: Write>(writer: &mut W, state: &SnapshotState) -> io::Result<()> {
+    put_u64(writer, state.created_at)?;
+    put_str(writer, &state.log_position.file)?;
+    put_u64(writer, state.log_position.offset)?;
+    put_u64(writer, state.version_seq)?;
+
+    put_u64(writer, state.clusters.len() as u64)?;
+    for cluster in state.clusters.iter() {
+        put_str(writer, &cluster.name)?;
+        put_u64(writer, cluster.entries.len() as u64)?;
+        for entry in cluster.entries.iter() {
+            put_str(writer, &entry.key)?;
+            put_bytes(writer, &entry.value)?;
+            writer.write_all(&[entry.value_type])?;
+            put_option(writer, entry.expires_at)?;
+            put_option(writer, entry.ttl)?;
+            put_u64(writer, entry.version)?;
+            put_u64(writer, entry.created_at)?;
+            put_u64(writer, entry.updated_at)?;
+            put_u64(writer, entry.last_access)?;
+            put_u64(writer, entry.access_count)?;
+            put_u64(writer, entry.tags.len() as u64)?;
+            for tag in entry.tags.iter() {
+                put_str(writer, tag)?;
+            }
+        }
+    }
+
+    put_u64(writer, state.aliases.len() as u64)?;
+    for (alias, target) in state.aliases.iter() {
+        put_str(writer, alias)?;
+        put_str(writer, target)?;
+    }
+    put_u64(writer, state.cluster_configs.len() as u64)?;
+    for (cluster, config) in state.cluster_configs.iter() {
+        put_str(writer, cluster)?;
+        put_str(writer, config)?;
+    }
+    put_u64(writer, state.cluster_expirations.len() as u64)?;
+    for (cluster, expires_at) in state.cluster_expirations.iter() {
+        put_str(writer, cluster)?;
+        put_u64(writer, *expires_at)?;
+    }
+    Ok(())
+}
+
+fn decode_snapshot<R: Read>(reader: &mut R) -> io::Result<SnapshotState> {
+    let mut magic = [0u8; 7];
+    reader.read_exact(&mut magic)?;
+    if &magic != SNAPSHOT_MAGIC {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            "not a snapshot file",
+        ));
+    }
+    match get_u8(reader)? {
+        SNAPSHOT_VERSION => decode_content(reader),
+        SEALED_SNAPSHOT_VERSION => {
+            let mut sealed = Vec::new();
+            reader.read_to_end(&mut sealed)?;
+            let content = encryption::keyring().open(&sealed).map_err(|error| {
+                let message = match error {
+                    OpenError::UnknownKey(id) => {
+                        format!("snapshot sealed with unknown encryption key {}", id)
+                    }
+                    OpenError::Rejected => "snapshot failed authentication".to_string(),
+                };
+                io::Error::new(ErrorKind::InvalidData, message)
+            })?;
+            decode_content(&mut content.as_slice())
+        }
+        version => Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("unsupported snapshot version {}", version),
+        )),
+    }
+}
+
+fn decode_content<R: Read>(reader: &mut R) -> io::Result<SnapshotState> {
+    let created_at = get_u64(reader)?;
+    let log_position = LogPosition {
+        file: get_str(reader)?,
+        offset: get_u64(reader)?,
+    };
+    let version_seq = get_u64(reader)?;
+
+    let mut clusters = Vec::new();
+    for _ in 0..get_u64(reader)? {
+        let name = get_str(reader)?;
+        let mut entries = Vec::new();
+        for _ in 0..get_u64(reader)? {
+            let key = get_str(reader)?;
+            let value = get_bytes(reader)?;
+            let value_type = get_u8(reader)?;
+            let expires_at = get_option(reader)?;
+            let ttl = get_option(reader)?;
+            let version = get_u64(reader)?;
+            let created_at = get_u64(reader)?;
+            let updated_at = get_u64(reader)?;
+            let last_access = get_u64(reader)?;
+            let access_count = get_u64(reader)?;
+            let mut tags = Vec::new();
+            for _ in 0..get_u64(reader)? {
+                tags.push(get_str(reader)?);
+            }
+            entries.push(SnapshotEntry {
+                key,
+                value,
+                value_type,
+                expires_at,
+                ttl,
+                version,
+                created_at,
+                updated_at,
+                last_access,
+                access_count,
+                tags,
+            });
+        }
+        clusters.push(SnapshotCluster { name, entries });
+    }
+
+    let mut aliases = Vec::new();
+    for _ in 0..get_u64(reader)? {
+        aliases.push((get_str(reader)?, get_str(reader)?));
+    }
+    let mut cluster_configs = Vec::new();
+    for _ in 0..get_u64(reader)? {
+        cluster_configs.push((get_str(reader)?, get_str(reader)?));
+    }
+    let mut cluster_expirations = Vec::new();
+    for _ in 0..get_u64(reader)? {
+        cluster_expirations.push((get_str(reader)?, get_u64(reader)?));
+    }
+
+    Ok(SnapshotState {
+        created_at,
+        log_position,
+        version_seq,
+        clusters,
+        aliases,
+        cluster_configs,
+        cluster_expirations,
+    })
+}
+
+fn put_u64<W: Write>(writer: &mut W, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn put_bytes<W: Write>(writer: &mut W, value: &[u8]) -> io::Result<()> {
+    put_u64(writer, value.len() as u64)?;
+    writer.write_all(value)
+}
+
+fn put_str<W: Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    put_bytes(writer, value.as_bytes())
+}
+
+fn put_option<W: Write>(writer: &mut W, value: Option<u64>) -> io::Result<()> {
+    match value {
+        Some(value) => {
+            writer.write_all(&[1])?;
+            put_u64(writer, value)
+        }
+        None => writer.write_all(&[0]),
+    }
+}
+
+fn get_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut buffer = [0u8; 1];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer[0])
+}
+
+fn get_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut buffer = [0u8; 8];
+    reader.read_exact(&mut buffer)?;
+    Ok(u64::from_le_bytes(buffer))
+}
+
+fn get_bytes<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let len = get_u64(reader)?;
+    let mut buffer = Vec::new();
+    reader.take(len).read_to_end(&mut buffer)?;
+    if buffer.len() as u64 != len {
+        return Err(io::Error::new(
+            ErrorKind::UnexpectedEof,
+            "snapshot is truncated",
+        ));
+    }
+    Ok(buffer)
+}
+
+fn get_str<R: Read>(reader: &mut R) -> io::Result<String> {
+    String::from_utf8(get_bytes(reader)?)
+        .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+}
+
+fn get_option<R: Read>(reader: &mut R) -> io::Result<Option<u64>> {
+    match get_u8(reader)? {
+        0 => Ok(None),
+        _ => get_u64(reader).map(Some),
+    }
+}
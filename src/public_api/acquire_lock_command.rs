@@ -0,0 +1,42 @@
+use super::server::AcquireLockRequest;
+use super::server::ApiResponse;
+use crate::cache::distributed_lock::DistributedLock;
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse};
+use base64::decode;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub async fn acquire_lock(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    payload: web::Json<AcquireLockRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let AcquireLockRequest { name, ttl, token } = &*payload;
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+
+    let result =
+        cache
+            .lock()
+            .unwrap()
+            .acquire_lock(name, Duration::from_millis(*ttl), token.clone());
+    match result {
+        Ok(result) if result.acquired => HttpResponse::Ok().json(ApiResponse::ok(result)),
+        Ok(result) => HttpResponse::Ok().json(ApiResponse::fail(result)),
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::fail(error.as_str())),
+    }
+}
@@ -1,5 +1,6 @@
 use super::server::ApiResponse;
 use super::server::UserRequest;
+use crate::cache::cache::CacheError;
 use crate::creds::auth::Authenticator;
 use crate::{
     cache::{
@@ -36,6 +37,10 @@ pub async fn clear_cluster(
     if !creds.lock().unwrap().authenticate(username, password) {
         return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
     }
-    cache.lock().unwrap().clear_cluster(&cluster, false);
+    let cache = cache.lock().unwrap();
+    if cache.is_cluster_read_only(&cache.resolve_cluster(&cluster)) {
+        return HttpResponse::Ok().json(ApiResponse::fail(CacheError::ReadOnlyCluster.as_str()));
+    }
+    cache.clear_cluster(&cluster, false);
     HttpResponse::Ok().json(ApiResponse::ok("Clear cluster operation successful"))
 }
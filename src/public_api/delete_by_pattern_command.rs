@@ -0,0 +1,56 @@
+use super::server::ApiResponse;
+use super::server::DeleteByPatternRequest;
+use crate::cache::key_pattern::KeyPattern;
+use crate::cache::keys_count::KeysCount;
+use crate::cache::scan::DEFAULT_SCAN_COUNT;
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use crate::jobs::delete_by_pattern_job::start_delete_by_pattern;
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse};
+use base64::decode;
+use std::sync::{Arc, Mutex};
+
+pub async fn delete_by_pattern(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    payload: web::Json<DeleteByPatternRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let DeleteByPatternRequest {
+        cluster,
+        pattern,
+        regex,
+        batch_size,
+    } = &*payload;
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+
+    let pattern = match KeyPattern::parse(pattern, regex.unwrap_or(false)) {
+        Ok(pattern) => pattern,
+        Err(message) => return HttpResponse::BadRequest().json(ApiResponse::fail(message)),
+    };
+    let total_keys = cache.lock().unwrap().keys_count(cluster);
+    if total_keys < 0 {
+        return HttpResponse::Ok().json(ApiResponse::fail("cluster not found"));
+    }
+
+    let progress = start_delete_by_pattern(
+        cache.get_ref().clone(),
+        cluster.clone(),
+        pattern,
+        batch_size.unwrap_or(DEFAULT_SCAN_COUNT),
+        total_keys,
+    );
+    HttpResponse::Accepted().json(ApiResponse::ok(progress))
+}
@@ -0,0 +1,34 @@
+use super::server::ApiResponse;
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse};
+use base64::decode;
+use std::sync::{Arc, Mutex};
+
+pub async fn delete_by_pattern_progress(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    job_id: web::Path<u64>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+
+    let cache = cache.lock().unwrap();
+    let progress = cache.delete_jobs.lock().unwrap().get(&job_id).cloned();
+    match progress {
+        Some(progress) => HttpResponse::Ok().json(ApiResponse::ok(progress)),
+        None => HttpResponse::NotFound().json(ApiResponse::fail("job not found")),
+    }
+}
@@ -0,0 +1,35 @@
+use super::server::ApiResponse;
+use crate::cache::drop_cluster::DropCluster;
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse};
+use base64::decode;
+use std::sync::{Arc, Mutex};
+
+pub async fn drop_cluster(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    info: web::Path<String>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let cluster = info.into_inner();
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+
+    if cache.lock().unwrap().drop_cluster(&cluster, false) {
+        HttpResponse::Ok().json(ApiResponse::ok("cluster dropped"))
+    } else {
+        HttpResponse::NotFound().json(ApiResponse::fail("cluster not found"))
+    }
+}
@@ -42,7 +42,7 @@ pub async fn expire_key(
     if !creds.lock().unwrap().authenticate(username, password) {
         return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
     }
-    let set_result = cache.lock().unwrap().expire_key(cluster, key, ttl);
+    let set_result = cache.lock().unwrap().expire_key(cluster, key, ttl, false);
 
     if set_result {
         HttpResponse::Ok().json(ApiResponse::ok("expire set"))
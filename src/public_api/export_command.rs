@@ -0,0 +1,47 @@
+use super::server::ApiResponse;
+use super::server::ExportQuery;
+use crate::cache::dump::DumpExport;
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use actix_web::{http::header::HeaderMap, web, web::Bytes, HttpRequest, HttpResponse};
+use base64::decode;
+use std::sync::{Arc, Mutex};
+
+// streams the dump as json lines, one page of keys at a time so large clusters never sit in one buffer
+pub async fn export(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    query: web::Query<ExportQuery>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+
+    let cache = cache.get_ref().clone();
+    let clusters: Vec<String> = query.cluster.clone().into_iter().collect();
+    let export = match DumpExport::new(&cache.lock().unwrap(), &clusters) {
+        Ok(export) => export,
+        Err(message) => return HttpResponse::Ok().json(ApiResponse::fail(message)),
+    };
+    let stream = futures_util::stream::unfold((cache, export), |(cache, mut export)| async move {
+        let chunk = export.next_chunk(&cache.lock().unwrap())?;
+        Some((
+            Ok::<_, actix_web::Error>(Bytes::from(chunk)),
+            (cache, export),
+        ))
+    });
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(stream)
+}
@@ -0,0 +1,37 @@
+use super::server::ApiResponse;
+use crate::cache::cluster_config::ClusterSettings;
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse};
+use base64::decode;
+use std::sync::{Arc, Mutex};
+
+pub async fn get_cluster_config(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    info: web::Path<String>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let cluster = info.into_inner();
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+
+    // clusters without settings report the defaults
+    let config = cache
+        .lock()
+        .unwrap()
+        .get_cluster_config(&cluster)
+        .unwrap_or_default();
+    HttpResponse::Ok().json(ApiResponse::ok(config))
+}
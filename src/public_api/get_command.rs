@@ -40,6 +40,7 @@ pub async fn get(
         Some(ref value) => HttpResponse::Ok().json(ApiResponse::ok(ResultValue {
             value: result.value,
             value_type: result.value_type,
+            version: result.version,
         })),
         None => HttpResponse::NotFound().json(ApiResponse::fail("Key not found")),
     }
@@ -0,0 +1,38 @@
+use super::server::ApiResponse;
+use crate::cache::cache::CacheError;
+use crate::cache::get_del::GetDel;
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse};
+use base64::decode;
+use std::sync::{Arc, Mutex};
+
+pub async fn get_del(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    info: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let (cluster, key) = info.into_inner();
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+    let mut cache = cache.lock().unwrap();
+    if cache.is_cluster_read_only(&cache.resolve_cluster(&cluster)) {
+        return HttpResponse::Ok().json(ApiResponse::fail(CacheError::ReadOnlyCluster.as_str()));
+    }
+    let result = cache.get_del(&cluster, &key, false);
+    match result.value {
+        Some(_) => HttpResponse::Ok().json(ApiResponse::ok(result)),
+        None => HttpResponse::NotFound().json(ApiResponse::fail("Key not found")),
+    }
+}
@@ -1,5 +1,7 @@
 use super::server::ApiResponse;
+use super::server::KeysQuery;
 use super::server::UserRequest;
+use crate::cache::key_pattern::KeyPattern;
 use crate::{
     cache::{
         cache::ResultValue, clear_cluster::ClearCluster, decr::Decr, delete::Delete, get::Get,
@@ -23,9 +25,11 @@ pub async fn get_keys_of_cluster(
     cache: web::Data<Arc<Mutex<Cache>>>,
     creds: web::Data<Arc<Mutex<CredsManager>>>,
     cluster: web::Path<String>,
+    query: web::Query<KeysQuery>,
     req: HttpRequest,
 ) -> HttpResponse {
     let cluster_name = cluster.into_inner();
+    let KeysQuery { pattern, regex } = &*query;
     let headers: &HeaderMap = req.headers();
     let auth = headers.get("Authorization").unwrap().to_str().unwrap();
     let decoded_bytes = decode(auth.clone()).expect("Failed to decode Base64 string");
@@ -38,6 +42,15 @@ pub async fn get_keys_of_cluster(
     if !creds.lock().unwrap().authenticate(username, password) {
         return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
     }
-    let keys = cache.lock().unwrap().get_keys_of_cluster(&cluster_name);
+    let keys = match pattern {
+        Some(pattern) => match KeyPattern::parse(pattern, regex.unwrap_or(false)) {
+            Ok(pattern) => cache
+                .lock()
+                .unwrap()
+                .get_keys_matching(&cluster_name, &pattern),
+            Err(message) => return HttpResponse::BadRequest().json(ApiResponse::fail(message)),
+        },
+        None => cache.lock().unwrap().get_keys_of_cluster(&cluster_name),
+    };
     HttpResponse::Ok().json(ApiResponse::ok(keys))
 }
@@ -0,0 +1,50 @@
+use super::server::ApiResponse;
+use super::server::SetRequest;
+use crate::cache::get_set::GetSet;
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse};
+use base64::decode;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub async fn get_set(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    payload: web::Json<SetRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let SetRequest {
+        cluster,
+        key,
+        value,
+        ttl,
+        ..
+    } = &*payload;
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+
+    let result = cache.lock().unwrap().get_set(
+        cluster.clone(),
+        key.clone(),
+        Vec::from(value.as_bytes()),
+        ttl.map(Duration::from_millis),
+        false,
+    );
+    if result.written {
+        HttpResponse::Ok().json(ApiResponse::ok(result))
+    } else {
+        HttpResponse::Ok().json(ApiResponse::fail(result))
+    }
+}
@@ -0,0 +1,58 @@
+use super::server::ApiResponse;
+use super::server::ImportQuery;
+use crate::cache::dump::DumpImporter;
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse};
+use base64::decode;
+use futures_util::StreamExt;
+use std::sync::{Arc, Mutex};
+
+// reads the json lines dump as it arrives and imports every complete line right away
+pub async fn import(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    query: web::Query<ImportQuery>,
+    mut payload: web::Payload,
+    req: HttpRequest,
+) -> HttpResponse {
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+
+    let mut importer = DumpImporter::new(query.mode.unwrap_or_default());
+    let mut pending: Vec<u8> = Vec::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(error) => {
+                return HttpResponse::BadRequest().json(ApiResponse::fail(error.to_string()))
+            }
+        };
+        pending.extend_from_slice(&chunk);
+        // everything up to the last newline is complete, the rest waits for the next chunk
+        if let Some(end) = pending.iter().rposition(|byte| *byte == b'\n') {
+            let lines: Vec<u8> = pending.drain(..=end).collect();
+            let mut cache = cache.lock().unwrap();
+            for line in lines.split(|byte| *byte == b'\n') {
+                importer.import_line(&mut cache, &String::from_utf8_lossy(line));
+            }
+        }
+    }
+    importer.import_line(
+        &mut cache.lock().unwrap(),
+        &String::from_utf8_lossy(&pending),
+    );
+
+    HttpResponse::Ok().json(ApiResponse::ok(importer.summary))
+}
@@ -1,24 +1,64 @@
 pub mod acl_authenticate;
 pub mod acl_set_user;
+pub mod acquire_lock_command;
+pub mod append_command;
 pub mod check_connection_command;
+pub mod compare_and_swap_command;
+pub mod copy_key_command;
 pub mod decr_command;
 pub mod del_command;
+pub mod delete_by_pattern_command;
+pub mod delete_by_pattern_progress_command;
+pub mod exec_command;
+pub mod export_command;
+pub mod get_aliases_command;
 pub mod get_all_clusters_command;
+pub mod get_cluster_config_command;
 pub mod get_command;
+pub mod get_del_command;
+pub mod get_range_command;
 pub mod get_keys_command;
+pub mod get_set_command;
+pub mod import_command;
 pub mod incr_command;
+pub mod inspect_key_command;
+pub mod invalidate_tag_command;
+pub mod recover_command;
+pub mod rewrite_log_command;
 pub mod server;
+pub mod set_alias_command;
 pub mod set_cluster_command;
+pub mod set_cluster_config_command;
 pub mod set_command;
+pub mod set_range_command;
+pub mod snapshot_command;
+pub mod str_len_command;
+pub mod tagged_keys_command;
 pub use server::run_server;
+pub mod cluster_ttl_command;
 pub mod clr_command;
 pub mod delete_user_command;
+pub mod drop_cluster_command;
+pub mod expire_cluster_command;
 pub mod expire_key_command;
+pub mod extend_lock_command;
 pub mod key_exists;
 pub mod keys_count;
 pub mod load_users_command;
 pub mod load_users_from_file_command;
+pub mod lock_holder_command;
 pub mod move_cluster_values_commnad;
 pub mod move_dev_cluster_command;
+pub mod move_key_command;
+pub mod rate_limit_command;
+pub mod release_lock_command;
+pub mod remove_alias_command;
+pub mod rename_key_command;
+pub mod scan_clusters_command;
+pub mod scan_keys_command;
+pub mod script_eval_command;
+pub mod script_exists_command;
+pub mod script_load_command;
 pub mod type_of_key;
+pub mod watch_command;
 pub mod who_am_i_command;
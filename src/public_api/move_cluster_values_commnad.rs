@@ -29,6 +29,7 @@ pub async fn copy_cluster(
     let MoveClusterValueRequest {
         src_cluster,
         desc_cluster,
+        policy,
     } = &*payload;
     let headers: &HeaderMap = req.headers();
     let auth = headers.get("Authorization").unwrap().to_str().unwrap();
@@ -43,13 +44,14 @@ pub async fn copy_cluster(
         return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
     }
 
-    let set_result = cache
-        .lock()
-        .unwrap()
-        .copy_cluster(&src_cluster.as_str(), &desc_cluster.as_str());
-    if set_result {
-        HttpResponse::Ok().json(ApiResponse::ok("cluster pasted"))
-    } else {
-        HttpResponse::Ok().json(ApiResponse::fail("operation failed"))
+    let set_result = cache.lock().unwrap().copy_cluster(
+        src_cluster.as_str(),
+        desc_cluster.as_str(),
+        policy.unwrap_or_default(),
+        false,
+    );
+    match set_result {
+        Ok(result) => HttpResponse::Ok().json(ApiResponse::ok(result)),
+        Err(error) => HttpResponse::Ok().json(ApiResponse::fail(error.as_str())),
     }
 }
@@ -0,0 +1,63 @@
+use super::server::ApiResponse;
+use super::server::RateLimitRequest;
+use crate::cache::rate_limit::RateLimit;
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse};
+use base64::decode;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub async fn rate_limit(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    payload: web::Json<RateLimitRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let RateLimitRequest {
+        cluster,
+        key,
+        algorithm,
+        limit,
+        window,
+        cost,
+    } = &*payload;
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+
+    if *limit == 0 || *window == 0 {
+        return HttpResponse::BadRequest().json(ApiResponse::fail(
+            "limit and window must be greater than zero",
+        ));
+    }
+
+    let cost = cost.unwrap_or(1);
+    if cost > *limit {
+        return HttpResponse::BadRequest().json(ApiResponse::fail("cost can not exceed limit"));
+    }
+
+    let result = cache.lock().unwrap().rate_limit(
+        cluster,
+        key,
+        *algorithm,
+        *limit,
+        Duration::from_millis(*window),
+        cost,
+    );
+    if result.allowed {
+        HttpResponse::Ok().json(ApiResponse::ok(result))
+    } else {
+        HttpResponse::Ok().json(ApiResponse::fail(result))
+    }
+}
@@ -0,0 +1,80 @@
+use super::server::ApiResponse;
+use super::server::RecoverRequest;
+use crate::cache::point_in_time::{parse_point_in_time, PointInTime, PointInTimeResult};
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use crate::jobs::snapshot_job::PERSISTENCE_JOB_IN_PROGRESS;
+use crate::logger::logger_manager::Logger;
+use crate::persistent::persistent_Manager;
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse};
+use base64::decode;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+
+// puts clusters back the way the persistence history says they were at a point in time
+pub async fn recover(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    body: web::Json<RecoverRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+
+    let until = match parse_point_in_time(&body.until) {
+        Ok(until) => until,
+        Err(message) => return HttpResponse::BadRequest().json(ApiResponse::fail(message)),
+    };
+    if !cache.lock().unwrap().persistent {
+        return HttpResponse::Ok().json(ApiResponse::fail("persistence is disabled"));
+    }
+    // a log rewrite removes the files being replayed, so the two never run together
+    if PERSISTENCE_JOB_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+        return HttpResponse::Ok().json(ApiResponse::fail(
+            "a snapshot or log rewrite is already in progress",
+        ));
+    }
+
+    let _ = persistent_Manager::flush_persistent_file(false);
+    let mut recovered = cache.lock().unwrap().recovery_cache();
+    let replay = match recovered.replay_until(until) {
+        Ok(replay) => replay,
+        Err(message) => {
+            PERSISTENCE_JOB_IN_PROGRESS.store(false, Ordering::SeqCst);
+            return HttpResponse::BadRequest().json(ApiResponse::fail(message));
+        }
+    };
+    let clusters = body.clusters.clone().unwrap_or_default();
+    let restore = cache
+        .lock()
+        .unwrap()
+        .restore_clusters(&recovered, &clusters);
+    PERSISTENCE_JOB_IN_PROGRESS.store(false, Ordering::SeqCst);
+
+    match restore {
+        Ok(restore) => {
+            let message = format!(
+                "clusters {:?} recovered to {} with {} keys",
+                restore.replaced_clusters, until, restore.imported
+            );
+            Logger::log_info_data(&message).write_log_to_file();
+            HttpResponse::Ok().json(ApiResponse::ok(PointInTimeResult {
+                until,
+                replay,
+                restore,
+            }))
+        }
+        Err(message) => HttpResponse::Ok().json(ApiResponse::fail(message)),
+    }
+}
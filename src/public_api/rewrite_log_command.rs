@@ -0,0 +1,39 @@
+use super::server::ApiResponse;
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use crate::jobs::log_rewrite_job::start_log_rewrite;
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse};
+use base64::decode;
+use std::sync::{Arc, Mutex};
+
+pub async fn rewrite_log(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+
+    if !cache.lock().unwrap().persistent {
+        return HttpResponse::Ok().json(ApiResponse::fail("persistence is disabled"));
+    }
+
+    if start_log_rewrite(cache.get_ref().clone()) {
+        HttpResponse::Accepted().json(ApiResponse::ok("log rewrite started"))
+    } else {
+        HttpResponse::Ok().json(ApiResponse::fail(
+            "a snapshot or log rewrite is already in progress",
+        ))
+    }
+}
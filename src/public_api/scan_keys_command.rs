@@ -0,0 +1,56 @@
+use super::server::ApiResponse;
+use super::server::ScanQuery;
+use crate::cache::key_pattern::KeyPattern;
+use crate::cache::scan::{Scan, DEFAULT_SCAN_COUNT};
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse};
+use base64::decode;
+use std::sync::{Arc, Mutex};
+
+pub async fn scan_keys(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    cluster: web::Path<String>,
+    query: web::Query<ScanQuery>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let cluster_name = cluster.into_inner();
+    let ScanQuery {
+        cursor,
+        count,
+        pattern,
+        regex,
+    } = &*query;
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+
+    let pattern = match pattern {
+        Some(pattern) => match KeyPattern::parse(pattern, regex.unwrap_or(false)) {
+            Ok(pattern) => Some(pattern),
+            Err(message) => return HttpResponse::BadRequest().json(ApiResponse::fail(message)),
+        },
+        None => None,
+    };
+    let result = cache.lock().unwrap().scan_keys(
+        &cluster_name,
+        cursor.as_deref(),
+        count.unwrap_or(DEFAULT_SCAN_COUNT),
+        pattern.as_ref(),
+    );
+    match result {
+        Ok(page) => HttpResponse::Ok().json(ApiResponse::ok(page)),
+        Err(message) => HttpResponse::Ok().json(ApiResponse::fail(message)),
+    }
+}
@@ -0,0 +1,48 @@
+use super::server::ApiResponse;
+use super::server::ScriptEvalRequest;
+use crate::cache::script::Script;
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse};
+use base64::decode;
+use std::sync::{Arc, Mutex};
+
+pub async fn script_eval(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    payload: web::Json<ScriptEvalRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let ScriptEvalRequest { script, sha, args } = &*payload;
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+
+    let mut cache = cache.lock().unwrap();
+    let sha = match (script, sha) {
+        (Some(script), _) => match cache.script_load(script) {
+            Ok(sha) => sha,
+            Err(message) => return HttpResponse::BadRequest().json(ApiResponse::fail(message)),
+        },
+        (None, Some(sha)) => sha.clone(),
+        (None, None) => {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::fail("either script or sha is required"))
+        }
+    };
+
+    match cache.script_eval(&sha, args.clone().unwrap_or_default()) {
+        Ok(result) => HttpResponse::Ok().json(ApiResponse::ok(result)),
+        Err(message) => HttpResponse::Ok().json(ApiResponse::fail(message)),
+    }
+}
@@ -1,3 +1,7 @@
+use crate::cache::dump::ImportMode;
+use crate::cache::move_cluster::ClusterWritePolicy;
+use crate::cache::rate_limit::RateLimitAlgorithm;
+use crate::cache::transaction::{TransactionCommand, WatchedKey};
 use crate::{
     cache::{
         cache::ResultValue, clear_cluster::ClearCluster, decr::Decr, delete::Delete, get::Get,
@@ -27,10 +31,28 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use super::{
-    delete_user_command::delete_user, expire_key_command::expire_key, key_exists::key_exists,
-    keys_count::keys_count, load_users_from_file_command::load_users_from_file,
+    acquire_lock_command::acquire_lock, append_command::append, cluster_ttl_command::cluster_ttl,
+    compare_and_swap_command::compare_and_swap, copy_key_command::copy_key,
+    delete_by_pattern_command::delete_by_pattern,
+    delete_by_pattern_progress_command::delete_by_pattern_progress,
+    delete_user_command::delete_user, drop_cluster_command::drop_cluster, exec_command::exec,
+    expire_cluster_command::expire_cluster, expire_key_command::expire_key, export_command::export,
+    extend_lock_command::extend_lock, get_aliases_command::get_aliases,
+    get_cluster_config_command::get_cluster_config, get_del_command::get_del,
+    get_range_command::get_range, get_set_command::get_set, import_command::import,
+    inspect_key_command::inspect_key, invalidate_tag_command::invalidate_tag,
+    key_exists::key_exists, keys_count::keys_count,
+    load_users_from_file_command::load_users_from_file, lock_holder_command::lock_holder,
     move_cluster_values_commnad::copy_cluster, move_dev_cluster_command::move_cluster,
-    type_of_key::type_of_key, who_am_i_command::who_am_i,
+    move_key_command::move_key, rate_limit_command::rate_limit, recover_command::recover,
+    release_lock_command::release_lock, remove_alias_command::remove_alias,
+    rename_key_command::rename_key, rewrite_log_command::rewrite_log,
+    scan_clusters_command::scan_clusters, scan_keys_command::scan_keys,
+    script_eval_command::script_eval, script_exists_command::script_exists,
+    script_load_command::script_load, set_alias_command::set_alias,
+    set_cluster_config_command::set_cluster_config, set_range_command::set_range,
+    snapshot_command::snapshot, str_len_command::str_len, tagged_keys_command::tagged_keys,
+    type_of_key::type_of_key, watch_command::watch, who_am_i_command::who_am_i,
 };
 
 #[derive(Deserialize)]
@@ -68,6 +90,183 @@ pub struct SetRequest {
     pub key: String,
     pub value: String,
     pub ttl: Option<u64>, // Duration in milliseconds
+    pub nx: Option<bool>, // only set if the key does not exist
+    pub xx: Option<bool>, // only set if the key already exists
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+pub struct CompareAndSwapRequest {
+    pub cluster: String,
+    pub key: String,
+    pub value: String,
+    pub version: u64,     // 0 when the key is expected to be absent
+    pub ttl: Option<u64>, // Duration in milliseconds
+}
+
+#[derive(Deserialize)]
+pub struct WatchRequest {
+    pub keys: Vec<TypeOfKeyRequest>,
+}
+
+#[derive(Deserialize)]
+pub struct ExecRequest {
+    pub watch: Option<Vec<WatchedKey>>,
+    pub commands: Vec<TransactionCommand>,
+}
+
+#[derive(Deserialize)]
+pub struct AcquireLockRequest {
+    pub name: String,
+    pub ttl: u64,              // Duration in milliseconds
+    pub token: Option<String>, // generated when missing
+}
+
+#[derive(Deserialize)]
+pub struct ReleaseLockRequest {
+    pub name: String,
+    pub token: String,
+}
+
+#[derive(Deserialize)]
+pub struct ExtendLockRequest {
+    pub name: String,
+    pub token: String,
+    pub ttl: u64, // Duration in milliseconds
+}
+
+#[derive(Deserialize)]
+pub struct RateLimitRequest {
+    pub cluster: String,
+    pub key: String,
+    pub algorithm: RateLimitAlgorithm,
+    pub limit: u64,
+    pub window: u64,       // Duration in milliseconds
+    pub cost: Option<u64>, // defaults to 1
+}
+
+#[derive(Deserialize)]
+pub struct ScriptLoadRequest {
+    pub script: String,
+}
+
+#[derive(Deserialize)]
+pub struct ScriptEvalRequest {
+    pub script: Option<String>, // loaded on the fly when given
+    pub sha: Option<String>,
+    pub args: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+pub struct ScanQuery {
+    pub cursor: Option<String>,
+    pub count: Option<usize>,
+    pub pattern: Option<String>,
+    pub regex: Option<bool>, // pattern is a glob unless set
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    pub cluster: Option<String>, // every cluster when missing
+}
+
+#[derive(Deserialize)]
+pub struct ImportQuery {
+    pub mode: Option<ImportMode>,
+}
+
+#[derive(Deserialize)]
+pub struct RecoverRequest {
+    pub until: String,                 // unix milliseconds or an RFC 3339 date
+    pub clusters: Option<Vec<String>>, // every cluster when missing
+}
+
+#[derive(Deserialize)]
+pub struct KeysQuery {
+    pub pattern: Option<String>,
+    pub regex: Option<bool>, // pattern is a glob unless set
+}
+
+#[derive(Deserialize)]
+pub struct DeleteByPatternRequest {
+    pub cluster: String,
+    pub pattern: String,
+    pub regex: Option<bool>,
+    pub batch_size: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct AppendRequest {
+    pub cluster: String,
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Deserialize)]
+pub struct SetRangeRequest {
+    pub cluster: String,
+    pub key: String,
+    pub offset: usize,
+    pub value: String,
+}
+
+#[derive(Deserialize)]
+pub struct RangeQuery {
+    pub start: i64,
+    pub end: i64, // inclusive, negative values count from the end
+}
+
+#[derive(Deserialize)]
+pub struct RenameKeyRequest {
+    pub cluster: String,
+    pub key: String,
+    pub new_key: String,
+    pub nx: Option<bool>, // only rename if the new key does not exist
+}
+
+#[derive(Deserialize)]
+pub struct CopyKeyRequest {
+    pub src_cluster: String,
+    pub key: String,
+    pub desc_cluster: String,
+    pub desc_key: Option<String>, // defaults to the source key
+    pub replace: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct MoveKeyRequest {
+    pub src_cluster: String,
+    pub key: String,
+    pub desc_cluster: String,
+    pub replace: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct ClusterConfigRequest {
+    pub cluster: String,
+    pub default_ttl: Option<u64>, // Duration in milliseconds
+    pub max_keys: Option<usize>,
+    pub eviction_strategy: Option<i32>,
+    pub read_only: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct ExpireClusterRequest {
+    pub cluster: String,
+    pub ttl: Option<u64>, // Duration in milliseconds, none removes the expiry
+}
+
+#[derive(Deserialize)]
+pub struct InvalidateTagRequest {
+    pub tag: String,
+    pub ttl: Option<u64>, // Duration in milliseconds, expires the keys instead of deleting them
+}
+
+#[derive(Deserialize)]
+pub struct SetAliasRequest {
+    pub alias: String,
+    pub cluster: String,
+    pub drop_previous: Option<bool>, // drop the cluster the alias pointed to before
 }
 
 #[derive(Deserialize)]
@@ -92,6 +291,7 @@ pub struct ExpireKeyRequest {
 pub struct MoveClusterValueRequest {
     pub src_cluster: String,
     pub desc_cluster: String,
+    pub policy: Option<ClusterWritePolicy>,
 }
 
 #[derive(Deserialize)]
@@ -114,12 +314,32 @@ pub async fn run_server(
             .app_data(web::Data::new(cache.clone()))
             .app_data(web::Data::new(creds.clone()))
             .route("/api/set", web::post().to(set))
+            .route("/api/getset", web::post().to(get_set))
+            .route("/api/cas", web::post().to(compare_and_swap))
+            .route("/api/watch", web::post().to(watch))
+            .route("/api/exec", web::post().to(exec))
+            .route("/api/lock/acquire", web::post().to(acquire_lock))
+            .route("/api/lock/release", web::post().to(release_lock))
+            .route("/api/lock/extend", web::post().to(extend_lock))
+            .route("/api/lock/{name}", web::get().to(lock_holder))
+            .route("/api/rate_limit", web::post().to(rate_limit))
+            .route("/api/script/load", web::post().to(script_load))
+            .route("/api/script/eval", web::post().to(script_eval))
+            .route("/api/script/exists/{sha}", web::get().to(script_exists))
             .route("/api/typeof", web::post().to(type_of_key))
             .route("/api/exists", web::post().to(key_exists))
             .route("/api/keys_count", web::post().to(keys_count))
             .route("/api/expire", web::post().to(expire_key))
             .route("/api/move_cluster", web::post().to(move_cluster))
             .route("/api/copy_cluster", web::post().to(copy_cluster))
+            .route("/api/alias", web::post().to(set_alias))
+            .route("/api/alias/{alias}", web::delete().to(remove_alias))
+            .route("/api/aliases", web::get().to(get_aliases))
+            .route("/api/invalidate_tag", web::post().to(invalidate_tag))
+            .route("/api/tag/{tag}", web::get().to(tagged_keys))
+            .route("/api/rename", web::post().to(rename_key))
+            .route("/api/copy_key", web::post().to(copy_key))
+            .route("/api/move_key", web::post().to(move_key))
             .route("/api/load_users", web::get().to(load_users))
             .route(
                 "/api/load_users_from_file",
@@ -128,10 +348,21 @@ pub async fn run_server(
             .route("/api/who_am_i", web::get().to(who_am_i))
             .route("/api/incr", web::post().to(incr))
             .route("/api/decr", web::post().to(decr))
+            .route("/api/append", web::post().to(append))
+            .route("/api/setrange", web::post().to(set_range))
+            .route("/api/getrange/{cluster}/{key}", web::get().to(get_range))
+            .route("/api/strlen/{cluster}/{key}", web::get().to(str_len))
             .route("/api/get/{cluster}/{key}", web::get().to(get))
+            .route("/api/inspect/{cluster}/{key}", web::get().to(inspect_key))
             .route("/api/delete_user/{username}", web::delete().to(delete_user))
             .route("/api/ping", web::get().to(check_connection))
+            .route("/api/snapshot", web::post().to(snapshot))
+            .route("/api/rewrite_log", web::post().to(rewrite_log))
+            .route("/api/export", web::get().to(export))
+            .route("/api/import", web::post().to(import))
+            .route("/api/recover", web::post().to(recover))
             .route("/api/delete/{cluster}/{key}", web::delete().to(delete))
+            .route("/api/getdel/{cluster}/{key}", web::delete().to(get_del))
             .route(
                 "/api/get_keys/{cluster}",
                 web::get().to(get_keys_of_cluster),
@@ -140,8 +371,26 @@ pub async fn run_server(
                 "/api/clear_cluster/{cluster}",
                 web::delete().to(clear_cluster),
             )
+            .route(
+                "/api/drop_cluster/{cluster}",
+                web::delete().to(drop_cluster),
+            )
+            .route("/api/expire_cluster", web::post().to(expire_cluster))
+            .route("/api/cluster_ttl/{cluster}", web::get().to(cluster_ttl))
             .route("/api/get_clusters", web::get().to(get_all_clusters))
+            .route("/api/scan/{cluster}", web::get().to(scan_keys))
+            .route("/api/scan_clusters", web::get().to(scan_clusters))
+            .route("/api/delete_by_pattern", web::post().to(delete_by_pattern))
+            .route(
+                "/api/delete_by_pattern/{job_id}",
+                web::get().to(delete_by_pattern_progress),
+            )
             .route("/api/set_cluster/{cluster}", web::post().to(set_cluster))
+            .route("/api/cluster_config", web::post().to(set_cluster_config))
+            .route(
+                "/api/cluster_config/{cluster}",
+                web::get().to(get_cluster_config),
+            )
             .route("/api/add_user", web::post().to(add_user))
             .route("/api/login", web::post().to(authenticate_user))
     })
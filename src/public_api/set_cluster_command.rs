@@ -36,6 +36,6 @@ pub async fn set_cluster(
     if !creds.lock().unwrap().authenticate(username, password) {
         return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
     }
-    cache.lock().unwrap().set_cluster(cluster_name);
+    cache.lock().unwrap().set_cluster(cluster_name, false);
     HttpResponse::Ok().json(ApiResponse::ok("Cluster set operation successful"))
 }
@@ -0,0 +1,51 @@
+use super::server::ApiResponse;
+use super::server::ClusterConfigRequest;
+use crate::cache::cluster_config::{ClusterConfig, ClusterSettings};
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse};
+use base64::decode;
+use std::sync::{Arc, Mutex};
+
+pub async fn set_cluster_config(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    payload: web::Json<ClusterConfigRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let ClusterConfigRequest {
+        cluster,
+        default_ttl,
+        max_keys,
+        eviction_strategy,
+        read_only,
+    } = &*payload;
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+
+    let config = ClusterConfig {
+        default_ttl: *default_ttl,
+        max_keys: *max_keys,
+        eviction_strategy: *eviction_strategy,
+        read_only: read_only.unwrap_or(false),
+    };
+    match cache
+        .lock()
+        .unwrap()
+        .set_cluster_config(cluster, config, false)
+    {
+        Ok(config) => HttpResponse::Ok().json(ApiResponse::ok(config)),
+        Err(error) => HttpResponse::BadRequest().json(ApiResponse::fail(error.as_str())),
+    }
+}
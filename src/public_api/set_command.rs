@@ -1,6 +1,9 @@
 use super::server::ApiResponse;
 use super::server::SetRequest;
 use super::server::UserRequest;
+use crate::cache::cache::CacheError;
+use crate::cache::conditional_set::{ConditionalSet, SetCondition};
+use crate::cache::tag_index::{valid_tag, Tagging};
 use crate::creds::auth::Authenticator;
 use crate::{
     cache::{
@@ -29,6 +32,9 @@ pub async fn set(
         key,
         value,
         ttl,
+        nx,
+        xx,
+        tags,
     } = &*payload;
     let headers: &HeaderMap = req.headers();
     let auth = headers.get("Authorization").unwrap().to_str().unwrap();
@@ -45,7 +51,41 @@ pub async fn set(
 
     let set_value = value.as_bytes();
     let ttl_duration = ttl.map(Duration::from_millis);
-    let set_result = cache.lock().unwrap().set(
+    let nx = nx.unwrap_or(false);
+    let xx = xx.unwrap_or(false);
+    if nx && xx {
+        return HttpResponse::BadRequest().json(ApiResponse::fail("nx and xx cannot be combined"));
+    }
+    if !tags.iter().flatten().all(|tag| valid_tag(tag)) {
+        return HttpResponse::BadRequest().json(ApiResponse::fail(CacheError::InvalidTag.as_str()));
+    }
+    let mut cache = cache.lock().unwrap();
+    if nx || xx {
+        let condition = if nx {
+            SetCondition::IfAbsent
+        } else {
+            SetCondition::IfExists
+        };
+        let outcome = cache.set_if(
+            cluster.clone(),
+            key.clone(),
+            Vec::from(set_value),
+            ttl_duration,
+            condition,
+            false,
+        );
+        return if outcome.written {
+            if let Some(tags) = tags {
+                if let Err(error) = cache.tag_key(cluster, key, tags, false) {
+                    return HttpResponse::Ok().json(ApiResponse::fail(error.as_str()));
+                }
+            }
+            HttpResponse::Ok().json(ApiResponse::ok(outcome))
+        } else {
+            HttpResponse::Ok().json(ApiResponse::fail(outcome))
+        };
+    }
+    let set_result = cache.set(
         cluster.clone(),
         key.clone(),
         Vec::from(set_value),
@@ -54,6 +94,11 @@ pub async fn set(
     );
 
     if set_result {
+        if let Some(tags) = tags {
+            if let Err(error) = cache.tag_key(cluster, key, tags, false) {
+                return HttpResponse::Ok().json(ApiResponse::fail(error.as_str()));
+            }
+        }
         HttpResponse::Ok().json(ApiResponse::ok("Set operation successful"))
     } else {
         HttpResponse::Ok().json(ApiResponse::fail("Set operation failed"))
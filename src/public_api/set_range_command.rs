@@ -0,0 +1,47 @@
+use super::server::ApiResponse;
+use super::server::SetRangeRequest;
+use crate::cache::cache::CacheError;
+use crate::cache::set_range::SetRange;
+use crate::cache::Cache;
+use crate::creds::auth::Authenticator;
+use crate::creds::cred_manager::CredsManager;
+use actix_web::{http::header::HeaderMap, web, HttpRequest, HttpResponse};
+use base64::decode;
+use std::sync::{Arc, Mutex};
+
+pub async fn set_range(
+    cache: web::Data<Arc<Mutex<Cache>>>,
+    creds: web::Data<Arc<Mutex<CredsManager>>>,
+    payload: web::Json<SetRangeRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let SetRangeRequest {
+        cluster,
+        key,
+        offset,
+        value,
+    } = &*payload;
+    let headers: &HeaderMap = req.headers();
+    let auth = headers.get("Authorization").unwrap().to_str().unwrap();
+    let decoded_bytes = decode(auth).expect("Failed to decode Base64 string");
+    let decoded_credentials =
+        std::str::from_utf8(&decoded_bytes).expect("Failed to convert bytes to string");
+    let creds_vec: Vec<&str> = decoded_credentials.split(":").collect();
+    let username = creds_vec.first().unwrap();
+    let password = creds_vec.get(1).unwrap();
+
+    if !creds.lock().unwrap().authenticate(username, password) {
+        return HttpResponse::Unauthorized().json(ApiResponse::fail("Authentication failed"));
+    }
+
+    match cache
+        .lock()
+        .unwrap()
+        .set_range(cluster, key, *offset, value.as_bytes(), false)
+    {
+        Ok(length) => HttpResponse::Ok().json(ApiResponse::ok(length)),
+        Err(CacheError::OffsetOutOfRange) => HttpResponse::BadRequest()
+            .json(ApiResponse::fail(CacheError::OffsetOutOfRange.as_str())),
+        Err(error) => HttpResponse::Ok().json(ApiResponse::fail(error.as_str())),
+    }
+}